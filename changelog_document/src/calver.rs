@@ -0,0 +1,442 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use anyhow::bail;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Deserializer};
+
+/// Component of a CalVer format. See <https://calver.org>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalVerToken {
+    /// Full year: 2006, 2016, 2106
+    FullYear,
+    /// Short year: 6, 16, 106
+    ShortYear,
+    /// Zero-padded year: 06, 16, 106
+    ZeroPaddedYear,
+    /// Short month: 1, 2 ... 11, 12
+    ShortMonth,
+    /// Zero-padded month: 01, 02 ... 11, 12
+    ZeroPaddedMonth,
+    /// Short ISO week: 1, 2, 33, 52. The year of the format is then the ISO week-numbering year.
+    ShortWeek,
+    /// Zero-padded ISO week: 01, 02, 33, 52
+    ZeroPaddedWeek,
+    /// Short day: 1, 2 ... 30, 31
+    ShortDay,
+    /// Zero-padded day: 01, 02 ... 30, 31
+    ZeroPaddedDay,
+    /// Incremented for each release of the same date.
+    Micro,
+}
+
+impl CalVerToken {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CalVerToken::FullYear => "YYYY",
+            CalVerToken::ShortYear => "YY",
+            CalVerToken::ZeroPaddedYear => "0Y",
+            CalVerToken::ShortMonth => "MM",
+            CalVerToken::ZeroPaddedMonth => "0M",
+            CalVerToken::ShortWeek => "WW",
+            CalVerToken::ZeroPaddedWeek => "0W",
+            CalVerToken::ShortDay => "DD",
+            CalVerToken::ZeroPaddedDay => "0D",
+            CalVerToken::Micro => "MICRO",
+        }
+    }
+
+    fn is_zero_padded(&self) -> bool {
+        matches!(
+            self,
+            CalVerToken::ZeroPaddedYear
+                | CalVerToken::ZeroPaddedMonth
+                | CalVerToken::ZeroPaddedWeek
+                | CalVerToken::ZeroPaddedDay
+        )
+    }
+
+    fn is_week(&self) -> bool {
+        matches!(self, CalVerToken::ShortWeek | CalVerToken::ZeroPaddedWeek)
+    }
+
+    /// With `iso_year`, the year is the one of the ISO week: 2024-12-30 is in the week 1 of 2025.
+    fn value(&self, date: NaiveDate, iso_year: bool) -> anyhow::Result<u64> {
+        let year = if iso_year {
+            date.iso_week().year()
+        } else {
+            date.year()
+        } as u64;
+
+        let value = match self {
+            CalVerToken::FullYear => year,
+            CalVerToken::ShortYear | CalVerToken::ZeroPaddedYear => match year.checked_sub(2000) {
+                Some(year) => year,
+                None => bail!("{} can't represent the year {year}", self.as_str()),
+            },
+            CalVerToken::ShortMonth | CalVerToken::ZeroPaddedMonth => date.month() as u64,
+            CalVerToken::ShortWeek | CalVerToken::ZeroPaddedWeek => date.iso_week().week() as u64,
+            CalVerToken::ShortDay | CalVerToken::ZeroPaddedDay => date.day() as u64,
+            CalVerToken::Micro => 0,
+        };
+
+        Ok(value)
+    }
+
+    fn format(&self, value: u64) -> String {
+        if self.is_zero_padded() {
+            format!("{value:02}")
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl FromStr for CalVerToken {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token = match s {
+            "YYYY" => CalVerToken::FullYear,
+            "YY" => CalVerToken::ShortYear,
+            "0Y" => CalVerToken::ZeroPaddedYear,
+            "MM" => CalVerToken::ShortMonth,
+            "0M" => CalVerToken::ZeroPaddedMonth,
+            "WW" => CalVerToken::ShortWeek,
+            "0W" => CalVerToken::ZeroPaddedWeek,
+            "DD" => CalVerToken::ShortDay,
+            "0D" => CalVerToken::ZeroPaddedDay,
+            "MICRO" => CalVerToken::Micro,
+            _ => bail!("Invalid CalVer token {s}. Expected one of YYYY, YY, 0Y, MM, 0M, WW, 0W, DD, 0D, MICRO"),
+        };
+
+        Ok(token)
+    }
+}
+
+/// Format of a CalVer version. Example: `YYYY.0M.MICRO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalVerFormat {
+    tokens: Vec<CalVerToken>,
+}
+
+impl FromStr for CalVerFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s
+            .split('.')
+            .map(CalVerToken::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self { tokens })
+    }
+}
+
+impl Display for CalVerFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = self
+            .tokens
+            .iter()
+            .map(CalVerToken::as_str)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        write!(f, "{format}")
+    }
+}
+
+impl<'de> Deserialize<'de> for CalVerFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        CalVerFormat::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl CalVerFormat {
+    /// Parse a version following this format. A modifier can follow the version: `2024.04.1-dev`.
+    pub fn parse(&self, s: &str) -> anyhow::Result<CalVer> {
+        let (version, modifier) = match s.split_once('-') {
+            Some((version, modifier)) => (version, Some(modifier.to_owned())),
+            None => (s, None),
+        };
+
+        let parts = version.split('.').collect::<Vec<_>>();
+
+        if parts.len() != self.tokens.len() {
+            bail!("{s} doesn't follow the CalVer format {self}");
+        }
+
+        let mut values = Vec::new();
+
+        for (part, token) in parts.iter().zip(&self.tokens) {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                bail!("{s} doesn't follow the CalVer format {self}");
+            }
+
+            if token.is_zero_padded() && part.len() < 2 {
+                bail!("{s} doesn't follow the CalVer format {self}: {part} is not zero-padded");
+            }
+
+            values.push(part.parse::<u64>()?);
+        }
+
+        Ok(CalVer::new(values, modifier, s.to_owned()))
+    }
+
+    /// Version for a release at this date. If `prev` has the same date, increment its micro.
+    pub fn next(&self, prev: Option<&CalVer>, date: NaiveDate) -> anyhow::Result<CalVer> {
+        let iso_year = self.tokens.iter().any(CalVerToken::is_week);
+
+        let mut parts = self
+            .tokens
+            .iter()
+            .map(|token| token.value(date, iso_year))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if let Some(prev) = prev {
+            let same_date = self
+                .tokens
+                .iter()
+                .zip(parts.iter().zip(&prev.parts))
+                .filter(|(token, _)| **token != CalVerToken::Micro)
+                .all(|(_, (new, prev))| new == prev);
+
+            if same_date {
+                if !self.tokens.contains(&CalVerToken::Micro) {
+                    bail!("{prev} was already released in this period. Add MICRO to the CalVer format {self}");
+                }
+
+                for ((token, new), prev) in self.tokens.iter().zip(&mut parts).zip(&prev.parts) {
+                    if *token == CalVerToken::Micro {
+                        *new = prev + 1;
+                    }
+                }
+            }
+        }
+
+        let raw = self
+            .tokens
+            .iter()
+            .zip(&parts)
+            .map(|(token, value)| token.format(*value))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        Ok(CalVer::new(parts, None, raw))
+    }
+}
+
+/// Compare two modifiers, with their numbers compared as numbers: `rc9 < rc10`.
+fn cmp_modifier(a: &str, b: &str) -> Ordering {
+    fn chunks(s: &str) -> Vec<&str> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+
+        for (i, c) in s.char_indices().skip(1) {
+            let prev = s[..i].chars().next_back().unwrap();
+            if prev.is_ascii_digit() != c.is_ascii_digit() {
+                chunks.push(&s[start..i]);
+                start = i;
+            }
+        }
+        chunks.push(&s[start..]);
+
+        chunks
+    }
+
+    let a_chunks = chunks(a);
+    let b_chunks = chunks(b);
+
+    for (a, b) in a_chunks.iter().zip(&b_chunks) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a_chunks.len().cmp(&b_chunks.len()).then_with(|| a.cmp(b))
+}
+
+/// Calendar version. The modifier is the equivalent of a semver pre-release.
+#[derive(Debug, Clone)]
+pub struct CalVer {
+    pub parts: Vec<u64>,
+    pub modifier: Option<String>,
+    raw: String,
+    /// Approximation for the code which only understand semver.
+    semver: semver::Version,
+}
+
+impl CalVer {
+    fn new(parts: Vec<u64>, modifier: Option<String>, raw: String) -> Self {
+        let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+
+        let mut semver = semver::Version::new(part(0), part(1), part(2));
+        if let Some(modifier) = &modifier {
+            semver.pre = semver::Prerelease::new(modifier).unwrap_or_default();
+        }
+
+        Self {
+            parts,
+            modifier,
+            raw,
+            semver,
+        }
+    }
+
+    pub fn semver(&self) -> &semver::Version {
+        &self.semver
+    }
+
+    /// The same version, without the modifier.
+    pub fn stable(&self) -> CalVer {
+        let raw = match self.raw.split_once('-') {
+            Some((raw, _)) => raw.to_owned(),
+            None => self.raw.clone(),
+        };
+
+        CalVer::new(self.parts.clone(), None, raw)
+    }
+}
+
+impl PartialEq for CalVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CalVer {}
+
+impl PartialOrd for CalVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CalVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.parts
+            .cmp(&other.parts)
+            .then_with(|| match (&self.modifier, &other.modifier) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => cmp_modifier(a, b),
+            })
+    }
+}
+
+impl Display for CalVer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let format = CalVerFormat::from_str("YYYY.0M.MICRO").unwrap();
+        assert_eq!(format.to_string(), "YYYY.0M.MICRO");
+
+        let v = format.parse("2024.04.1").unwrap();
+        assert_eq!(v.parts, vec![2024, 4, 1]);
+        assert_eq!(v.to_string(), "2024.04.1");
+
+        format.parse("2024.4.1").unwrap_err();
+        format.parse("2024.04").unwrap_err();
+
+        let dev = format.parse("2024.04.1-dev").unwrap();
+        assert_eq!(dev.modifier, Some("dev".into()));
+        assert!(dev < v);
+
+        assert!(format.parse("2024.04.10").unwrap() > format.parse("2024.04.9").unwrap());
+
+        assert!(format.parse("2024.04.1-rc10").unwrap() > format.parse("2024.04.1-rc9").unwrap());
+        assert!(
+            format.parse("2024.04.1-beta").unwrap() > format.parse("2024.04.1-alpha2").unwrap()
+        );
+        assert!(
+            format.parse("2024.04.1-rc.2").unwrap() < format.parse("2024.04.1-rc.2.1").unwrap()
+        );
+
+        CalVerFormat::from_str("YYYY.MONTH").unwrap_err();
+    }
+
+    #[test]
+    fn next() {
+        let format = CalVerFormat::from_str("YY.0M.MICRO").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 4, 12).unwrap();
+
+        assert_eq!(format.next(None, date).unwrap().to_string(), "24.04.0");
+
+        let prev = format.parse("24.04.0").unwrap();
+        assert_eq!(
+            format.next(Some(&prev), date).unwrap().to_string(),
+            "24.04.1"
+        );
+
+        let prev = format.parse("24.03.5").unwrap();
+        assert_eq!(
+            format.next(Some(&prev), date).unwrap().to_string(),
+            "24.04.0"
+        );
+
+        let format = CalVerFormat::from_str("YYYY.0M.0D").unwrap();
+        assert_eq!(format.next(None, date).unwrap().to_string(), "2024.04.12");
+
+        // no micro to increment in the same period
+        let format = CalVerFormat::from_str("YYYY.0M").unwrap();
+        let prev = format.parse("2024.04").unwrap();
+        format.next(Some(&prev), date).unwrap_err();
+        let prev = format.parse("2024.03").unwrap();
+        assert_eq!(
+            format.next(Some(&prev), date).unwrap().to_string(),
+            "2024.04"
+        );
+
+        // the last days of December can be in the first week of the next year
+        let format = CalVerFormat::from_str("YYYY.0W").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 12, 30).unwrap();
+        let prev = format.parse("2024.52").unwrap();
+        let next = format.next(Some(&prev), date).unwrap();
+        assert_eq!(next.to_string(), "2025.01");
+        assert!(next > prev);
+        assert_eq!(
+            CalVerFormat::from_str("YYYY.0M")
+                .unwrap()
+                .next(None, date)
+                .unwrap()
+                .to_string(),
+            "2024.12"
+        );
+
+        // short years start in 2000
+        let date = NaiveDate::from_ymd_opt(1999, 12, 31).unwrap();
+        CalVerFormat::from_str("YY.MM")
+            .unwrap()
+            .next(None, date)
+            .unwrap_err();
+        CalVerFormat::from_str("0Y.MM")
+            .unwrap()
+            .next(None, date)
+            .unwrap_err();
+        let date = NaiveDate::from_ymd_opt(2006, 1, 1).unwrap();
+        assert_eq!(
+            CalVerFormat::from_str("0Y.MM")
+                .unwrap()
+                .next(None, date)
+                .unwrap()
+                .to_string(),
+            "06.1"
+        );
+    }
+}
@@ -1,20 +1,22 @@
-use std::str::FromStr;
-
-use crate::utils::UNRELEASED;
+use crate::utils::{SOURCE_MARKER, UNRELEASED, YANKED};
 
 use super::*;
+use chrono::NaiveDate;
 use pom::parser::*;
 use utils::*;
 
 #[derive(Debug, Clone)]
 pub struct Options {
     pub unreleased: String,
+    /// Parse versions with this format as CalVer versions.
+    pub calver: Option<CalVerFormat>,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             unreleased: String::from(UNRELEASED),
+            calver: None,
         }
     }
 }
@@ -63,7 +65,8 @@ pub(crate) fn changelog_parser(options: &Options) -> Parser<'_, char, ChangeLog>
                 continue;
             }
 
-            let version = match Version::from_str(&release.title.version) {
+            let version = match Version::parse_with(&release.title.version, options.calver.as_ref())
+            {
                 Ok(v) => v,
                 Err(e) => return Err(format!("not valid semver {e}")),
             };
@@ -89,15 +92,22 @@ pub(crate) fn release_title<'a>() -> Parser<'a, char, ReleaseTitle> {
 
     let release_link = sym('(') * none_of("\n)").repeat(1..) - sym(')');
 
-    let title = sym(' ') * sym('-') * sym(' ') * none_of("\n]").repeat(1..);
+    let title = sym(' ') * sym('-') * sym(' ') * none_of("\n").repeat(1..);
+
+    let yanked = sym(' ') * tag(YANKED);
+
+    let parser = version + release_link.opt() + title.opt() + yanked.opt();
 
-    let parser = version + release_link.opt() + title.opt();
+    parser.convert(|(((version, release_link), title), yanked)| {
+        let (title, yanked_title) = split_yanked(title.map(into_string));
+        let (date, title) = split_date(title);
 
-    parser.convert(|((version, release_link), title)| {
         let res = ReleaseTitle {
             version: into_string(version),
-            title: title.map(into_string),
+            date,
+            title,
             release_link: release_link.map(into_string),
+            yanked: yanked.is_some() || yanked_title,
         };
 
         Ok::<ReleaseTitle, ()>(res)
@@ -116,16 +126,75 @@ pub(crate) fn release_section_note<'a>() -> Parser<'a, char, ReleaseSectionNote>
         + context;
 
     parser.convert(|((scope, note), context)| {
+        let (message, source) = split_source(into_string(note));
+
         let res = ReleaseSectionNote {
             scope: scope.map(into_string),
-            message: into_string(note),
+            message,
             context: context.into_iter().map(into_string).collect(),
+            source,
         };
 
         Ok::<ReleaseSectionNote, ()>(res)
     })
 }
 
+/// `2024-05-01 - title`
+/// `2024-05-01 [YANKED]`
+fn split_yanked(title: Option<String>) -> (Option<String>, bool) {
+    match title
+        .as_deref()
+        .and_then(|title| title.strip_suffix(YANKED))
+    {
+        Some(rest) => {
+            let rest = rest.trim_end();
+            ((!rest.is_empty()).then(|| rest.to_owned()), true)
+        }
+        None => (title, false),
+    }
+}
+
+fn split_date(title: Option<String>) -> (Option<NaiveDate>, Option<String>) {
+    let Some(title) = title else {
+        return (None, None);
+    };
+
+    let date = title
+        .get(..10)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+
+    let Some(date) = date else {
+        return (None, Some(title));
+    };
+
+    let title = title[10..].trim_start();
+    let title = title.strip_prefix("- ").unwrap_or(title).trim();
+
+    (Some(date), (!title.is_empty()).then(|| title.to_owned()))
+}
+
+/// `message <!-- changen:sha #pr -->`
+fn split_source(note: String) -> (String, Option<NoteSource>) {
+    let Some((message, marker)) = note
+        .strip_suffix("-->")
+        .and_then(|note| note.rsplit_once(SOURCE_MARKER))
+    else {
+        return (note, None);
+    };
+
+    let mut source = NoteSource::default();
+
+    for token in marker.split_whitespace() {
+        if token.starts_with('#') {
+            source.pr = Some(token.to_owned());
+        } else {
+            source.sha = Some(token.to_owned());
+        }
+    }
+
+    (message.trim_end().to_owned(), Some(source))
+}
+
 pub(crate) fn release_section<'a>() -> Parser<'a, char, ReleaseSection> {
     let title = space() * sym('#').repeat(3) * sym(' ') * none_of("\n").repeat(1..) - sym('\n');
 
@@ -1,6 +1,6 @@
 use std::mem;
 
-use indexmap::{IndexMap, IndexSet};
+use indexmap::IndexMap;
 
 use crate::{ChangeLog, Release, ReleaseSectionNote};
 
@@ -36,13 +36,16 @@ impl ChangeLog {
 impl Release {
     pub fn deduplicate(&mut self) {
         for (_, sec) in &mut self.note_sections {
-            let mut deduplicator = IndexSet::new();
+            let mut deduplicator = IndexMap::new();
 
+            // the hidden source is not compared
             for n in sec.notes.drain(..) {
-                deduplicator.insert(n);
+                deduplicator
+                    .entry((n.scope.clone(), n.message.clone(), n.context.clone()))
+                    .or_insert(n);
             }
 
-            sec.notes.extend(deduplicator);
+            sec.notes.extend(deduplicator.into_values());
         }
     }
 
@@ -1,12 +1,15 @@
 use std::collections::BTreeMap;
 
+use chrono::NaiveDate;
 use indexmap::IndexMap;
 
+mod calver;
 pub mod de;
 pub mod fmt;
 pub mod ser;
 pub mod utils;
 mod version;
+pub use calver::{CalVer, CalVerFormat, CalVerToken};
 pub use semver;
 pub use version::Version;
 
@@ -17,7 +20,12 @@ mod test;
 pub struct ReleaseTitle {
     pub version: String,
     pub release_link: Option<String>,
+    /// `## [1.0.0] - 2024-05-01`
+    pub date: Option<NaiveDate>,
+    /// Free text after the date.
     pub title: Option<String>,
+    /// `## [1.0.0] - 2024-05-01 [YANKED]`
+    pub yanked: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,6 +39,18 @@ pub struct ReleaseSectionNote {
     pub scope: Option<String>,
     pub message: String,
     pub context: Vec<String>,
+    /// Where this note was generated from, even if it was edited since.
+    pub source: Option<NoteSource>,
+}
+
+/// Commit and PR of a generated note.
+/// Written in a hidden marker: `<!-- changen:1a2b3c4 #12 -->`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NoteSource {
+    /// Short sha of the commit.
+    pub sha: Option<String>,
+    /// Example: `#12`.
+    pub pr: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -74,7 +94,9 @@ impl ChangeLog {
         let unreleased = Release {
             title: ReleaseTitle {
                 version: String::from("Unreleased"),
+                date: None,
                 title: None,
+                yanked: false,
                 release_link: None,
             },
             header: None,
@@ -89,7 +111,9 @@ impl ChangeLog {
             Release {
                 title: ReleaseTitle {
                     version: version.to_string(),
+                    date: None,
                     title: None,
+                    yanked: false,
                     release_link: None,
                 },
                 header: None,
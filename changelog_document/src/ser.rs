@@ -1,4 +1,7 @@
-use crate::*;
+use crate::{
+    utils::{SOURCE_MARKER, YANKED},
+    *,
+};
 
 // todo: use io::Write
 
@@ -71,10 +74,18 @@ pub fn serialize_release(s: &mut String, release: &Release, options: &OptionsRel
             full_title.push_str(&format!("({})", release_link));
         }
 
+        if let Some(date) = &release.title.date {
+            full_title.push_str(&format!(" - {}", date.format("%Y-%m-%d")));
+        }
+
         if let Some(title) = &release.title.title {
             full_title.push_str(&format!(" - {}", title));
         }
 
+        if release.title.yanked {
+            full_title.push_str(&format!(" {}", YANKED));
+        }
+
         full_title.push('\n');
 
         s.push_str(&full_title);
@@ -114,13 +125,25 @@ pub fn serialize_release(s: &mut String, release: &Release, options: &OptionsRel
 }
 
 pub fn serialize_release_section_note(s: &mut String, note: &ReleaseSectionNote) {
-    let note_title = if let Some(scope) = &note.scope {
-        format!("- {}: {}\n", scope, note.message)
+    let mut note_title = if let Some(scope) = &note.scope {
+        format!("- {}: {}", scope, note.message)
     } else {
-        format!("- {}\n", note.message)
+        format!("- {}", note.message)
     };
 
+    if let Some(source) = &note.source {
+        let marker = [&source.sha, &source.pr]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        note_title.push_str(&format!(" {SOURCE_MARKER}{marker} -->"));
+    }
+
     s.push_str(&note_title);
+    s.push('\n');
 
     for context in &note.context {
         s.push_str(&format!("  {}\n", context));
@@ -147,6 +170,7 @@ mod test {
             scope: Some("data".into()),
             message: "the program".into(),
             context: vec!["- fix la base".into(), "49-3 hihi".into()],
+            source: None,
         };
 
         let mut output = String::new();
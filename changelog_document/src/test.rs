@@ -42,7 +42,9 @@ pub static CHANGELOG1: LazyLock<ChangeLog> = LazyLock::new(|| ChangeLog {
             Release {
                 title: ReleaseTitle {
                     version: version.to_string(),
+                    date: None,
                     title: Some("i'm am the title of the night".into()),
+                    yanked: false,
                     release_link: None,
                 },
                 header: Some("header".into()),
@@ -63,26 +65,31 @@ pub static CHANGELOG1: LazyLock<ChangeLog> = LazyLock::new(|| ChangeLog {
                                         "49-3 hihi".into(),
                                         "lol".into(),
                                     ],
+                                    source: None,
                                 },
                                 ReleaseSectionNote {
                                     scope: Some("ui".into()),
                                     message: "the widget".into(),
                                     context: vec![],
+                                    source: None,
                                 },
                                 ReleaseSectionNote {
                                     scope: None,
                                     message: "lol".into(),
                                     context: vec![],
+                                    source: None,
                                 },
                                 ReleaseSectionNote {
                                     scope: Some("ui".into()),
                                     message: "the widget".into(),
                                     context: vec![],
+                                    source: None,
                                 },
                                 ReleaseSectionNote {
                                     scope: Some("data".into()),
                                     message: "the widget".into(),
                                     context: vec![],
+                                    source: None,
                                 },
                             ],
                         },
@@ -99,7 +106,9 @@ pub static CHANGELOG1: LazyLock<ChangeLog> = LazyLock::new(|| ChangeLog {
             Release {
                 title: ReleaseTitle {
                     version: version.to_string(),
+                    date: None,
                     title: None,
+                    yanked: false,
                     release_link: Some(
                         "https://github.com/wiiznokes/fan-control/releases/tag/v2024.7.30".into(),
                     ),
@@ -136,11 +145,58 @@ fn release_title() {
 
     let res = parser.parse(&f_input).unwrap();
 
+    assert_eq!(res.title.date, NaiveDate::from_ymd_opt(2024, 7, 24));
+    assert_eq!(res.title.title, None);
+
+    let mut s = String::new();
+
+    ser::serialize_release(&mut s, &res, &OptionsRelease::default());
+
+    assert_eq!(input, s);
+
+    let input = "## [1.0.0](https://github.com/wiiznokes/changen/releases/1.0.0) - 2024-05-01 - The first one\n";
+
+    let f_input = input.chars().collect::<Vec<_>>();
+
+    let res = de::release().parse(&f_input).unwrap();
+
+    assert_eq!(res.title.date, NaiveDate::from_ymd_opt(2024, 5, 1));
+    assert_eq!(res.title.title.as_deref(), Some("The first one"));
+
     let mut s = String::new();
 
     ser::serialize_release(&mut s, &res, &OptionsRelease::default());
 
     assert_eq!(input, s);
+
+    let input = "## [1.0.0] - The first one\n";
+
+    let f_input = input.chars().collect::<Vec<_>>();
+
+    let res = de::release().parse(&f_input).unwrap();
+
+    assert_eq!(res.title.date, None);
+    assert_eq!(res.title.title.as_deref(), Some("The first one"));
+    assert!(!res.title.yanked);
+
+    for input in [
+        "## [0.2.0] - 2024-01-01 [YANKED]\n",
+        "## [0.2.0] - 2024-01-01 - The first one [YANKED]\n",
+        "## [0.2.0] [YANKED]\n",
+    ] {
+        let f_input = input.chars().collect::<Vec<_>>();
+
+        let res = de::release().parse(&f_input).unwrap();
+
+        assert!(res.title.yanked);
+        assert_eq!(res.header, None);
+
+        let mut s = String::new();
+
+        ser::serialize_release(&mut s, &res, &OptionsRelease::default());
+
+        assert_eq!(input, s);
+    }
 }
 
 #[test]
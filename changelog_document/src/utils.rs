@@ -2,15 +2,23 @@ use std::{borrow::Cow, collections::btree_map, iter::Rev, sync::LazyLock};
 
 use anyhow::bail;
 
-use crate::{ChangeLog, Release, ReleaseSection, ReleaseTitle, Version};
+use crate::{ChangeLog, Release, ReleaseSection, ReleaseSectionNote, ReleaseTitle, Version};
 
 pub const UNRELEASED: &str = "Unreleased";
 
+/// Start of the hidden marker of [`ReleaseSectionNote::source`].
+pub const SOURCE_MARKER: &str = "<!-- changen:";
+
+/// Suffix of the title of a yanked release.
+pub const YANKED: &str = "[YANKED]";
+
 pub static DEFAULT_UNRELEASED: LazyLock<Release> = LazyLock::new(|| Release {
     title: ReleaseTitle {
         version: UNRELEASED.into(),
         release_link: None,
+        date: None,
         title: None,
+        yanked: false,
     },
     header: Default::default(),
     note_sections: Default::default(),
@@ -94,6 +102,29 @@ impl Release {
         &self.title.version
     }
 
+    /// A note was generated from the commit `sha`.
+    pub fn has_note_from(&self, sha: &str) -> bool {
+        self.note_sections
+            .values()
+            .any(|section| section.notes.iter().any(|note| note.is_from(sha)))
+    }
+
+    /// A note was generated from the PR `pr`.
+    pub fn has_note_from_pr(&self, pr: &str) -> bool {
+        self.note_sections
+            .values()
+            .any(|section| section.notes.iter().any(|note| note.is_from_pr(pr)))
+    }
+
+    /// Remove the hidden markers of the notes.
+    pub fn clear_sources(&mut self) {
+        for section in self.note_sections.values_mut() {
+            for note in &mut section.notes {
+                note.source = None;
+            }
+        }
+    }
+
     pub fn insert_release_notes<I>(&mut self, notes: I)
     where
         I: IntoIterator<Item = ReleaseSection>,
@@ -110,3 +141,22 @@ impl Release {
         }
     }
 }
+
+impl ReleaseSectionNote {
+    /// Generated from the commit `sha`. Both can be abbreviated.
+    pub fn is_from(&self, sha: &str) -> bool {
+        match self.source.as_ref().and_then(|source| source.sha.as_ref()) {
+            Some(source) if !source.is_empty() && !sha.is_empty() => {
+                source.starts_with(sha) || sha.starts_with(source.as_str())
+            }
+            _ => false,
+        }
+    }
+
+    /// Generated from the PR `pr`. Example: `#12`.
+    pub fn is_from_pr(&self, pr: &str) -> bool {
+        self.source
+            .as_ref()
+            .is_some_and(|source| source.pr.as_deref() == Some(pr))
+    }
+}
@@ -1,14 +1,18 @@
 use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
+use crate::calver::{CalVer, CalVerFormat};
+
 #[derive(Debug, Clone)]
 pub enum Version {
     Semver(semver::Version),
     PartialSemver(semver::Version, String),
+    /// Only parsed when a CalVer format is provided.
+    CalVer(CalVer),
 }
 
 impl PartialEq for Version {
     fn eq(&self, other: &Self) -> bool {
-        self.version().eq(other.version())
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -20,9 +24,16 @@ impl PartialOrd for Version {
     }
 }
 
+/// CalVer versions are always greater than the other versions,
+/// to support projects that switched to CalVer.
 impl Ord for Version {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.version().cmp(other.version())
+        match (self, other) {
+            (Version::CalVer(a), Version::CalVer(b)) => a.cmp(b),
+            (Version::CalVer(_), _) => Ordering::Greater,
+            (_, Version::CalVer(_)) => Ordering::Less,
+            _ => self.version().cmp(other.version()),
+        }
     }
 }
 
@@ -31,6 +42,7 @@ impl Display for Version {
         let v = match self {
             Version::Semver(v) => &v.to_string(),
             Version::PartialSemver(_, s) => s,
+            Version::CalVer(v) => &v.to_string(),
         };
 
         write!(f, "{}", v)
@@ -65,11 +77,23 @@ impl Version {
         Self::Semver(semver::Version::new(major, minor, patch))
     }
 
+    /// Parse `s` as a CalVer version if it follow `calver`, fallback to semver otherwise.
+    pub fn parse_with(s: &str, calver: Option<&CalVerFormat>) -> anyhow::Result<Self> {
+        if let Some(calver) = calver {
+            if let Ok(v) = calver.parse(s) {
+                return Ok(Self::CalVer(v));
+            }
+        }
+
+        Self::from_str(s)
+    }
+
     #[inline]
     pub fn version(&self) -> &semver::Version {
         match self {
             Version::Semver(v) => v,
             Version::PartialSemver(v, _) => v,
+            Version::CalVer(v) => v.semver(),
         }
     }
 
@@ -77,7 +101,35 @@ impl Version {
     pub fn version_opt(&self) -> Option<&semver::Version> {
         match self {
             Version::Semver(v) => Some(v),
-            Version::PartialSemver(..) => None,
+            Version::PartialSemver(..) | Version::CalVer(_) => None,
+        }
+    }
+
+    #[inline]
+    pub fn calver_opt(&self) -> Option<&CalVer> {
+        match self {
+            Version::CalVer(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Semver pre-release, or CalVer modifier.
+    pub fn is_pre_release(&self) -> bool {
+        match self {
+            Version::Semver(v) => !v.pre.is_empty(),
+            Version::PartialSemver(..) => false,
+            Version::CalVer(v) => v.modifier.is_some(),
+        }
+    }
+
+    /// `self` is a pre-release of the stable version `stable`.
+    pub fn is_pre_release_of(&self, stable: &Version) -> bool {
+        match (self, stable) {
+            (Version::Semver(v), Version::Semver(stable)) => {
+                v.major == stable.major && v.minor == stable.minor && v.patch == stable.patch
+            }
+            (Version::CalVer(v), Version::CalVer(stable)) => v.parts == stable.parts,
+            _ => false,
         }
     }
 }
@@ -86,7 +138,34 @@ impl Version {
 mod test {
     use std::str::FromStr;
 
-    use crate::version::Version;
+    use crate::{calver::CalVerFormat, version::Version};
+
+    #[test]
+    fn calver() {
+        let format = CalVerFormat::from_str("YYYY.0M.MICRO").unwrap();
+
+        let v = Version::parse_with("2024.04.1", Some(&format)).unwrap();
+        assert!(v.calver_opt().is_some());
+        assert_eq!(v.to_string(), "2024.04.1");
+
+        assert_ne!(
+            Version::parse_with("2024.04.1", Some(&format)).unwrap(),
+            Version::parse_with("2024.04.2", Some(&format)).unwrap()
+        );
+        assert!(
+            Version::parse_with("2024.04.2", Some(&format)).unwrap()
+                > Version::parse_with("2024.04.1", Some(&format)).unwrap()
+        );
+
+        let dev = Version::parse_with("2024.04.1-dev", Some(&format)).unwrap();
+        assert!(dev.is_pre_release());
+        assert!(dev.is_pre_release_of(&v));
+
+        assert!(v > Version::new(1, 2, 3));
+
+        let v = Version::parse_with("1.2.3", Some(&format)).unwrap();
+        assert_eq!(v, Version::new(1, 2, 3));
+    }
 
     #[test]
     fn test() {
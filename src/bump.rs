@@ -0,0 +1,321 @@
+use anyhow::{bail, Result};
+use changelog::{CalVerFormat, ChangeLog, Release, Version};
+
+use crate::{
+    commit_parser::parse_commit,
+    config::{BumpKind, MapMessageToSection, MapTarget},
+    generate::commit_should_be_ignored,
+    repository::{Period, RawCommit, Repository, TagFormat},
+};
+
+/// Version of the first release, when there is no previous one.
+const FIRST_VERSION: Version = Version::new(0, 1, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Patch,
+    Minor,
+    Major,
+}
+
+/// Compute the next version, from the Unreleased section if it has notes,
+/// or from the commits since the last release.
+/// With `pre`, compute the next pre-release of this version.
+/// With CalVer, the version only depends on the current date.
+pub fn next_version<R: Repository>(
+    r: &R,
+    changelog: &ChangeLog,
+    kind: &BumpKind,
+    pre: Option<&str>,
+    map: &MapMessageToSection,
+    breaking_section: &str,
+    tags: &TagFormat,
+) -> Result<Version> {
+    if let Some(calver) = &tags.calver {
+        if pre.is_some() {
+            bail!("pre-releases are not supported with CalVer");
+        }
+        return next_calver_version(r, changelog, calver, tags);
+    }
+
+    let version = next_stable_version(r, changelog, kind, map, breaking_section, tags)?;
+
+    match pre {
+        Some(pre) => pre_release_version(changelog, &version, pre),
+        None => Ok(version),
+    }
+}
+
+fn next_calver_version<R: Repository>(
+    r: &R,
+    changelog: &ChangeLog,
+    calver: &CalVerFormat,
+    tags: &TagFormat,
+) -> Result<Version> {
+    let prev = match changelog.last_version() {
+        Some(prev) => Some(prev),
+        None => r.tags_list(tags)?.pop_back(),
+    };
+
+    let date = chrono::Local::now().date_naive();
+
+    Ok(Version::CalVer(
+        calver.next(prev.as_ref().and_then(Version::calver_opt), date)?,
+    ))
+}
+
+fn next_stable_version<R: Repository>(
+    r: &R,
+    changelog: &ChangeLog,
+    kind: &BumpKind,
+    map: &MapMessageToSection,
+    breaking_section: &str,
+    tags: &TagFormat,
+) -> Result<Version> {
+    let prev = match changelog.last_version() {
+        Some(prev) => Some(prev),
+        None => r.tags_list(tags)?.pop_back(),
+    };
+
+    let Some(prev) = prev else {
+        return Ok(FIRST_VERSION);
+    };
+
+    // finish the series of pre-releases
+    if !prev.version().pre.is_empty() {
+        return Ok(bump_version(&prev, Level::Patch));
+    }
+
+    let level = match kind {
+        BumpKind::Major => Level::Major,
+        BumpKind::Minor => Level::Minor,
+        BumpKind::Patch => Level::Patch,
+        BumpKind::Auto => {
+            let level = match &changelog.unreleased {
+                Some(unreleased) if !unreleased.note_sections.is_empty() => {
+                    level_from_release(unreleased, map, breaking_section)
+                }
+                _ => {
+                    let commits = r.commits(&Period {
+                        since: Some(tags.tag(&prev)),
+                        until: None,
+                    })?;
+                    level_from_commits(&commits, map)
+                }
+            };
+
+            debug!("bump level: {:?}", level);
+
+            level.unwrap_or(Level::Patch)
+        }
+    };
+
+    Ok(bump_version(&prev, level))
+}
+
+pub fn level_from_release(
+    release: &Release,
+    map: &MapMessageToSection,
+    breaking_section: &str,
+) -> Option<Level> {
+    let feat_section = map.map_section("feat");
+
+    release
+        .note_sections
+        .values()
+        .filter(|section| !section.notes.is_empty())
+        .map(|section| {
+            if section.title == breaking_section
+                || section
+                    .notes
+                    .iter()
+                    .any(|note| note.message.contains("**BREAKING**"))
+            {
+                Level::Major
+            } else if Some(&section.title) == feat_section.as_ref() {
+                Level::Minor
+            } else {
+                Level::Patch
+            }
+        })
+        .max()
+}
+
+pub fn level_from_commits(commits: &[RawCommit], map: &MapMessageToSection) -> Option<Level> {
+    let feat_section = map.map_section("feat");
+
+    commits
+        .iter()
+        .filter(|raw| !commit_should_be_ignored(raw).bool())
+        .filter_map(|raw| match parse_commit(&raw.title, &raw.body) {
+            Ok(commit)
+                if map.map_commit(&commit.section, commit.scope.as_deref())
+                    == Some(MapTarget::Ignore) =>
+            {
+                None
+            }
+            Ok(commit) if commit.breaking => Some(Level::Major),
+            Ok(commit)
+                if feat_section.is_some() && map.map_section(&commit.section) == feat_section =>
+            {
+                Some(Level::Minor)
+            }
+            _ => Some(Level::Patch),
+        })
+        .max()
+}
+
+/// Next pre-release of `target`. Example: `1.2.0-beta.2` if `1.2.0-beta.1` is in the changelog.
+/// Going back to a previous identifier (from rc to beta) is refused.
+pub fn pre_release_version(changelog: &ChangeLog, target: &Version, id: &str) -> Result<Version> {
+    let target = target.version();
+
+    let same_target = changelog.releases.keys().filter_map(|k| {
+        k.version_opt().filter(|k| {
+            k.major == target.major
+                && k.minor == target.minor
+                && k.patch == target.patch
+                && !k.pre.is_empty()
+        })
+    });
+
+    let mut number = 0;
+    let mut last_pre: Option<&semver::Version> = None;
+
+    for k in same_target {
+        let (k_id, k_number) = match k.pre.as_str().split_once('.') {
+            Some((k_id, k_number)) => (k_id, k_number.parse::<u64>().unwrap_or(0)),
+            None => (k.pre.as_str(), 0),
+        };
+
+        if k_id == id {
+            number = number.max(k_number);
+        }
+
+        if last_pre.is_none_or(|last_pre| k > last_pre) {
+            last_pre = Some(k);
+        }
+    }
+
+    let mut new = semver::Version::new(target.major, target.minor, target.patch);
+    new.pre = semver::Prerelease::new(&format!("{id}.{}", number + 1))?;
+
+    if let Some(last_pre) = last_pre {
+        if *last_pre > new {
+            bail!("Can't release {new}, because {last_pre} already exist. Use a following pre-release identifier, or a stable version.");
+        }
+    }
+
+    Ok(Version::Semver(new))
+}
+
+/// Before 1.0.0, breaking changes bump the minor, and features the patch.
+/// A pre-release is promoted to its stable version.
+pub fn bump_version(prev: &Version, level: Level) -> Version {
+    let prev = prev.version();
+
+    if !prev.pre.is_empty() {
+        return Version::new(prev.major, prev.minor, prev.patch);
+    }
+
+    let level = match (prev.major, level) {
+        (0, Level::Major) => Level::Minor,
+        (0, Level::Minor) => Level::Patch,
+        (_, level) => level,
+    };
+
+    match level {
+        Level::Major => Version::new(prev.major + 1, 0, 0),
+        Level::Minor => Version::new(prev.major, prev.minor + 1, 0),
+        Level::Patch => Version::new(prev.major, prev.minor, prev.patch + 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use changelog::utils::DEFAULT_UNRELEASED;
+
+    use super::*;
+
+    fn raw_commit(title: &str) -> RawCommit {
+        RawCommit {
+            author: "wiiznokes".into(),
+            title: title.into(),
+            body: "".into(),
+            sha: "".into(),
+            list_files: vec![],
+            parents: vec![],
+        }
+    }
+
+    #[test]
+    fn bump() {
+        let v = |s: &str| Version::from_str(s).unwrap();
+
+        assert_eq!(bump_version(&v("1.2.3"), Level::Major), v("2.0.0"));
+        assert_eq!(bump_version(&v("1.2.3"), Level::Minor), v("1.3.0"));
+        assert_eq!(bump_version(&v("1.2.3"), Level::Patch), v("1.2.4"));
+
+        assert_eq!(bump_version(&v("0.2.3"), Level::Major), v("0.3.0"));
+        assert_eq!(bump_version(&v("0.2.3"), Level::Minor), v("0.2.4"));
+        assert_eq!(bump_version(&v("0.2.3"), Level::Patch), v("0.2.4"));
+
+        assert_eq!(bump_version(&v("1.0.0-beta.2"), Level::Patch), v("1.0.0"));
+    }
+
+    #[test]
+    fn pre_release() {
+        let v = |s: &str| Version::from_str(s).unwrap();
+
+        let mut changelog = ChangeLog::new();
+
+        assert_eq!(
+            pre_release_version(&changelog, &v("1.2.0"), "beta").unwrap(),
+            v("1.2.0-beta.1")
+        );
+
+        for version in ["1.1.0", "1.2.0-beta.1", "1.2.0-beta.2"] {
+            changelog
+                .releases
+                .insert(v(version), DEFAULT_UNRELEASED.clone());
+        }
+
+        assert_eq!(
+            pre_release_version(&changelog, &v("1.2.0"), "beta").unwrap(),
+            v("1.2.0-beta.3")
+        );
+        assert_eq!(
+            pre_release_version(&changelog, &v("1.2.0"), "rc").unwrap(),
+            v("1.2.0-rc.1")
+        );
+
+        changelog
+            .releases
+            .insert(v("1.2.0-rc.1"), DEFAULT_UNRELEASED.clone());
+
+        pre_release_version(&changelog, &v("1.2.0"), "beta").unwrap_err();
+        assert_eq!(
+            pre_release_version(&changelog, &v("1.3.0"), "alpha").unwrap(),
+            v("1.3.0-alpha.1")
+        );
+    }
+
+    #[test]
+    fn level() {
+        let map = MapMessageToSection::default();
+
+        let commits = vec![raw_commit("fix: 1"), raw_commit("chore: 2")];
+        assert_eq!(level_from_commits(&commits, &map), Some(Level::Patch));
+
+        let commits = vec![raw_commit("fix: 1"), raw_commit("feat: 2")];
+        assert_eq!(level_from_commits(&commits, &map), Some(Level::Minor));
+
+        let commits = vec![raw_commit("feat(api)!: 1"), raw_commit("feat: 2")];
+        assert_eq!(level_from_commits(&commits, &map), Some(Level::Major));
+
+        let commits = vec![raw_commit("feat!: 1 (skip changelog)")];
+        assert_eq!(level_from_commits(&commits, &map), None);
+    }
+}
@@ -1,32 +1,110 @@
+use std::sync::LazyLock;
+
 use pom::parser::*;
+use regex::Regex;
 use utils::{into_string, space};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FormattedCommit {
     pub section: String,
     pub scope: Option<String>,
+    /// Either the `!` marker or a `BREAKING CHANGE` footer.
+    pub breaking: bool,
     pub message: String,
+    /// Body of the commit, without the footers.
+    pub body: String,
+    pub footers: Vec<Footer>,
+}
+
+/// Footer/trailer of a commit. Example: `Reviewed-by: Z`, `Refs #123`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    pub token: String,
+    pub value: String,
+}
+
+impl Footer {
+    pub fn is_breaking_change(&self) -> bool {
+        self.token == "BREAKING CHANGE" || self.token == "BREAKING-CHANGE"
+    }
 }
 
-pub fn parse_commit(input: &str) -> anyhow::Result<FormattedCommit> {
-    let input = input.chars().collect::<Vec<_>>();
+pub fn parse_commit(title: &str, body: &str) -> anyhow::Result<FormattedCommit> {
+    let input = title.chars().collect::<Vec<_>>();
     let parser = commit_parser();
-    let commit = parser.parse(&input)?;
+    let mut commit = parser.parse(&input)?;
+
+    let (body, footers) = parse_body(body);
+
+    commit.breaking |= footers.iter().any(Footer::is_breaking_change);
+    commit.body = body;
+    commit.footers = footers;
 
     Ok(commit)
 }
 
+static FOOTER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(BREAKING[ -]CHANGE|[\w-]+)(?:: | #)(.*)$").unwrap());
+
+/// Split the body and the footers. Footers are the last paragraph,
+/// when it begins with a `token: value` or `token #value` line.
+pub fn parse_body(body: &str) -> (String, Vec<Footer>) {
+    let lines = body.trim_end().lines().collect::<Vec<_>>();
+
+    let last_paragraph = lines
+        .iter()
+        .rposition(|line| line.trim().is_empty())
+        .map_or(0, |pos| pos + 1);
+
+    let mut footers: Vec<Footer> = Vec::new();
+
+    if lines
+        .get(last_paragraph)
+        .is_some_and(|line| FOOTER_REGEX.is_match(line))
+    {
+        for line in &lines[last_paragraph..] {
+            match (FOOTER_REGEX.captures(line), footers.last_mut()) {
+                (Some(captures), _) => footers.push(Footer {
+                    token: captures[1].to_owned(),
+                    value: captures[2].to_owned(),
+                }),
+                (None, Some(footer)) => {
+                    footer.value.push('\n');
+                    footer.value.push_str(line);
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+
+    for footer in &mut footers {
+        footer.value = footer.value.trim().to_owned();
+    }
+
+    let content = if footers.is_empty() {
+        &lines[..]
+    } else {
+        &lines[..last_paragraph]
+    };
+
+    (content.join("\n").trim().to_owned(), footers)
+}
+
 fn commit_parser<'a>() -> Parser<'a, char, FormattedCommit> {
     let scope = space() * sym('(') * none_of("()").repeat(1..) - sym(')');
 
-    let parser = none_of(" :()").repeat(1..) + scope.opt() - space() * sym(':') * space()
+    let parser = none_of(" :()!").repeat(1..) + scope.opt() + sym('!').opt()
+        - space() * sym(':') * space()
         + any().repeat(1..);
 
-    parser.convert(|((section, scope), message)| {
+    parser.convert(|(((section, scope), breaking), message)| {
         let res = FormattedCommit {
             section: into_string(section),
             scope: scope.map(into_string),
+            breaking: breaking.is_some(),
             message: into_string(message),
+            body: String::new(),
+            footers: vec![],
         };
 
         Ok::<FormattedCommit, ()>(res)
@@ -64,7 +142,10 @@ mod test {
             Ok(FormattedCommit {
                 section: String::from("fix"),
                 scope: Some(String::from("hello")),
-                message: String::from("hihi")
+                breaking: false,
+                message: String::from("hihi"),
+                body: String::new(),
+                footers: vec![],
             })
         );
 
@@ -80,8 +161,81 @@ mod test {
             Ok(FormattedCommit {
                 section: String::from("improve"),
                 scope: Some(String::from("ignore")),
-                message: String::from("hihi")
+                breaking: false,
+                message: String::from("hihi"),
+                body: String::new(),
+                footers: vec![],
             })
         );
+
+        let m = map("feat(api)!: remove the old endpoint");
+        let commit = commit_parser().parse(&m).unwrap();
+        assert!(commit.breaking);
+        assert_eq!(commit.scope, Some(String::from("api")));
+        assert_eq!(commit.message, String::from("remove the old endpoint"));
+
+        let m = map("feat!: hihi");
+        let commit = commit_parser().parse(&m).unwrap();
+        assert!(commit.breaking);
+        assert_eq!(commit.section, String::from("feat"));
+        assert_eq!(commit.scope, None);
+    }
+
+    #[test]
+    fn footers() {
+        let commit = parse_commit(
+            "feat: allow provided config object to extend other configs",
+            "Some context about the change.
+
+Reviewed-by: Z
+BREAKING CHANGE: `extends` key in config file is now used
+for extending other config files
+Refs #133",
+        )
+        .unwrap();
+
+        assert!(commit.breaking);
+        assert_eq!(commit.body, String::from("Some context about the change."));
+        assert_eq!(
+            commit.footers,
+            vec![
+                Footer {
+                    token: String::from("Reviewed-by"),
+                    value: String::from("Z"),
+                },
+                Footer {
+                    token: String::from("BREAKING CHANGE"),
+                    value: String::from(
+                        "`extends` key in config file is now used\nfor extending other config files"
+                    ),
+                },
+                Footer {
+                    token: String::from("Refs"),
+                    value: String::from("133"),
+                },
+            ]
+        );
+
+        // not a footer because it is in the middle of a paragraph
+        let (body, footers) = parse_body("first line\nNote: not a footer\n\nSigned-off-by: me");
+        assert_eq!(body, String::from("first line\nNote: not a footer"));
+        assert_eq!(footers.len(), 1);
+
+        // only the last paragraph contains footers
+        let (body, footers) =
+            parse_body("first line\n\nNote: this also changes X\nand Y\n\nlast paragraph");
+        assert_eq!(
+            body,
+            String::from("first line\n\nNote: this also changes X\nand Y\n\nlast paragraph")
+        );
+        assert!(footers.is_empty());
+
+        let (body, footers) = parse_body("Note: this also changes X\n\nRefs #133\n");
+        assert_eq!(body, String::from("Note: this also changes X"));
+        assert_eq!(footers.len(), 1);
+
+        let commit = parse_commit("fix: hihi", "").unwrap();
+        assert!(!commit.breaking);
+        assert!(commit.footers.is_empty());
     }
 }
@@ -3,20 +3,168 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::{collections::HashSet, fmt::Display};
 
+use anyhow::{anyhow, bail};
 use changelog::fmt::SortOptions;
 use clap::{arg, Args, Parser, Subcommand, ValueHint};
 
 use changelog::ser::{Options, OptionsRelease};
-use changelog::Version;
+use changelog::{CalVerFormat, Version};
+use chrono::NaiveDate;
 use clap::ValueEnum;
 use indexmap::IndexMap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::git_provider::GitProvider;
+use crate::ignore::IgnoreRules;
+use crate::template::Templates;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MapMessageToSection(pub IndexMap<String, HashSet<String>>);
+/// Where a commit goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapTarget {
+    Section(String),
+    Ignore,
+}
+
+/// A rule of the rich map format.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MapRule {
+    /// Title of the section.
+    pub section: Option<String>,
+    /// Drop the matching commits, instead of sending them to a section.
+    pub ignore: bool,
+    /// Commit types: `fix`, `chore(deps)`, or `*(deps)` for any type.
+    pub types: Vec<String>,
+    /// Words searched in the title and the body, on word boundaries.
+    pub words: Vec<String>,
+    /// Regexes matched on the title only.
+    pub title: Vec<String>,
+    /// Regexes matched on the body only.
+    pub body: Vec<String>,
+    /// Rules with a higher priority are tried first. Default to 0.
+    pub priority: i64,
+}
+
+/// The rich map format.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RichMap {
+    /// Section of the commits matched by no rule. Default to "Unidentified".
+    pub fallback: Option<String>,
+    #[serde(alias = "rule")]
+    pub rules: Vec<MapRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum MapFormat {
+    /// Section title -> needles. Needles are matched on the commit type, and as words in the message.
+    Legacy(IndexMap<String, HashSet<String>>),
+    Rich(RichMap),
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    target: MapTarget,
+    types: Vec<String>,
+    title: Vec<Regex>,
+    body: Vec<Regex>,
+}
+
+impl CompiledRule {
+    fn new(rule: MapRule) -> anyhow::Result<Self> {
+        let target = match (rule.section, rule.ignore) {
+            (Some(section), false) => MapTarget::Section(section),
+            (None, true) => MapTarget::Ignore,
+            (Some(section), true) => {
+                bail!("The rule of the section {section} can't also be an ignore rule")
+            }
+            (None, false) => bail!("A rule needs a section, or ignore = true"),
+        };
+
+        let words = rule
+            .words
+            .iter()
+            .map(|word| format!(r"(?i)\b{}\b", regex::escape(word)))
+            .collect::<Vec<_>>();
+
+        let regexes = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Regex::new(pattern).map_err(|e| anyhow!("Invalid regex {pattern}: {e}"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            target,
+            types: rule.types.iter().map(|e| e.to_lowercase()).collect(),
+            title: regexes(&[words.clone(), rule.title].concat())?,
+            body: regexes(&[words, rule.body].concat())?,
+        })
+    }
+}
+
+/// Map commits to the sections of the changelog.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "MapFormat")]
+pub struct MapMessageToSection {
+    /// Sorted by priority.
+    rules: Vec<CompiledRule>,
+    /// In the order of the map.
+    sections: Vec<String>,
+    fallback: String,
+}
+
+impl TryFrom<MapFormat> for MapMessageToSection {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MapFormat) -> Result<Self, Self::Error> {
+        let map = match value {
+            MapFormat::Legacy(map) => RichMap {
+                fallback: None,
+                rules: map
+                    .into_iter()
+                    .map(|(section, needles)| MapRule {
+                        section: Some(section),
+                        words: needles
+                            .iter()
+                            .filter(|needle| split_scope(needle).is_none())
+                            .cloned()
+                            .collect(),
+                        types: needles.into_iter().collect(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+            MapFormat::Rich(map) => map,
+        };
+
+        let mut sections = Vec::new();
+
+        for rule in &map.rules {
+            if let Some(section) = &rule.section {
+                if !sections.contains(section) {
+                    sections.push(section.clone());
+                }
+            }
+        }
+
+        let mut rules = map.rules;
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+        Ok(Self {
+            rules: rules
+                .into_iter()
+                .map(CompiledRule::new)
+                .collect::<anyhow::Result<_>>()?,
+            sections,
+            fallback: map.fallback.unwrap_or(DEFAULT_FALLBACK_SECTION.into()),
+        })
+    }
+}
 
 impl Default for MapMessageToSection {
     fn default() -> Self {
@@ -25,11 +173,24 @@ impl Default for MapMessageToSection {
     }
 }
 
+pub const DEFAULT_BREAKING_SECTION: &str = "Breaking Changes";
+
+pub const DEFAULT_FALLBACK_SECTION: &str = "Unidentified";
+
 impl MapMessageToSection {
-    pub fn to_fmt_options(self) -> changelog::fmt::Options {
+    /// The breaking changes section always comes first.
+    pub fn to_fmt_options(self, breaking_section: &str) -> changelog::fmt::Options {
+        let section_order = std::iter::once(breaking_section.to_owned())
+            .chain(
+                self.sections
+                    .into_iter()
+                    .filter(|section| section != breaking_section),
+            )
+            .collect();
+
         changelog::fmt::Options {
             sort_options: SortOptions {
-                section_order: self.0.into_iter().map(|(section, _)| section).collect(),
+                section_order,
                 ..Default::default()
             },
         }
@@ -42,44 +203,69 @@ impl MapMessageToSection {
         }
     }
 
+    /// Titles of the sections, in the order of the map.
+    pub fn sections(&self) -> &[String] {
+        &self.sections
+    }
+
+    /// Section of the commits matched by no rule.
+    pub fn fallback(&self) -> &str {
+        &self.fallback
+    }
+
     pub fn map_section(&self, section: &str) -> Option<String> {
-        let section_normalized = section.to_lowercase();
+        match self.map_commit(section, None)? {
+            MapTarget::Section(section) => Some(section),
+            MapTarget::Ignore => None,
+        }
+    }
 
-        for (section, needles) in &self.0 {
-            for needle in needles {
-                let needle_normalized = needle.to_lowercase();
+    /// Needles can target a scope: `chore(deps)`, or `*(deps)` for any type.
+    /// They take precedence over the needles without scope.
+    pub fn map_commit(&self, section: &str, scope: Option<&str>) -> Option<MapTarget> {
+        if let Some(scope) = scope {
+            for rule in &self.rules {
+                for needle in &rule.types {
+                    let Some((needle_section, needle_scope)) = split_scope(needle) else {
+                        continue;
+                    };
 
-                if section_normalized == needle_normalized {
-                    return Some(section.to_owned());
+                    if needle_scope.eq_ignore_ascii_case(scope)
+                        && (needle_section == "*" || needle_section.eq_ignore_ascii_case(section))
+                    {
+                        return Some(rule.target.clone());
+                    }
                 }
             }
         }
 
-        None
+        let section_normalized = section.to_lowercase();
+
+        self.rules
+            .iter()
+            .find(|rule| rule.types.contains(&section_normalized))
+            .map(|rule| rule.target.clone())
     }
 
     /// Best effort recognition
-    pub fn try_find_section(&self, (message, desc): (&str, &str)) -> Option<String> {
-        let message_normalized = message.to_lowercase();
-        let desc_normalized = desc.to_lowercase();
-
-        for (section, needles) in &self.0 {
-            for needle in needles {
-                let needle_normalized = needle.to_lowercase();
+    pub fn try_find_section(&self, (message, desc): (&str, &str)) -> Option<MapTarget> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.title.iter().any(|regex| regex.is_match(message))
+                    || rule.body.iter().any(|regex| regex.is_match(desc))
+            })
+            .map(|rule| rule.target.clone())
+    }
 
-                if message_normalized.contains(&needle_normalized) {
-                    return Some(section.to_owned());
-                }
-                if desc_normalized.contains(&needle_normalized) {
-                    return Some(section.to_owned());
-                }
-            }
+    pub fn try_new<P: AsRef<Path>>(
+        path: Option<P>,
+        inline: Option<&MapMessageToSection>,
+    ) -> anyhow::Result<MapMessageToSection> {
+        if let Some(map) = inline {
+            return Ok(map.clone());
         }
 
-        None
-    }
-
-    pub fn try_new<P: AsRef<Path>>(path: Option<P>) -> anyhow::Result<MapMessageToSection> {
         match path {
             Some(path) => {
                 let mut file = File::open(&path)?;
@@ -96,7 +282,13 @@ impl MapMessageToSection {
     }
 }
 
+/// `type(scope)` -> `(type, scope)`
+fn split_scope(needle: &str) -> Option<(&str, &str)> {
+    needle.strip_suffix(')')?.split_once('(')
+}
+
 #[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 pub enum CommitMessageParsing {
     #[default]
     Smart,
@@ -115,6 +307,48 @@ impl Display for CommitMessageParsing {
 }
 
 #[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakingChanges {
+    /// Move breaking changes in a dedicated section
+    #[default]
+    Section,
+    /// Keep breaking changes in their section, with a **BREAKING** mark
+    Mark,
+}
+
+impl Display for BreakingChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreakingChanges::Section => write!(f, "section"),
+            BreakingChanges::Mark => write!(f, "mark"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BumpKind {
+    /// Major for breaking changes, minor for features, patch otherwise
+    #[default]
+    Auto,
+    Major,
+    Minor,
+    Patch,
+}
+
+impl Display for BumpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BumpKind::Auto => write!(f, "auto"),
+            BumpKind::Major => write!(f, "major"),
+            BumpKind::Minor => write!(f, "minor"),
+            BumpKind::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 pub enum MergeDevVersions {
     /// Yes if the version is stable, no otherwise
     #[default]
@@ -138,6 +372,12 @@ impl Display for MergeDevVersions {
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Path to the configuration file. By default, search for changen.toml, then for a [package.metadata.changen] section in Cargo.toml, at the root of the repo.
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+    /// Use calendar versioning with this format. Example: YYYY.0M.MICRO. See https://calver.org
+    #[arg(long, global = true)]
+    pub calver: Option<CalVerFormat>,
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -147,19 +387,20 @@ pub enum Commands {
     #[command(alias = "gen")]
     Generate(Generate),
     Release(Release),
+    Bump(Bump),
     Show(Show),
     #[command(aliases = ["delete", "rm"])]
     Remove(Remove),
+    Yank(Yank),
 }
 
 /// Generate release notes. By default, generate from the last release in the changelog to HEAD.
 #[derive(Debug, Clone, Args)]
 pub struct Generate {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
         short_alias = 'o',
         alias = "output",
@@ -168,27 +409,82 @@ pub struct Generate {
     /// Path to the commit type to changelog section map.
     #[arg(long, value_hint = ValueHint::FilePath)]
     pub map: Option<PathBuf>,
-    /// Parsing of the commit message.
-    #[arg(long, default_value_t)]
-    pub parsing: CommitMessageParsing,
-    /// Don't include unidentified commits.
+    /// Section map defined inline in the configuration file.
+    #[arg(skip)]
+    pub map_inline: Option<MapMessageToSection>,
+    /// Templates defined in the configuration file.
+    #[arg(skip)]
+    pub templates: Templates,
+    /// Parsing of the commit message. Default to smart.
     #[arg(long)]
-    pub exclude_unidentified: bool,
+    pub parsing: Option<CommitMessageParsing>,
+    /// Don't include unidentified commits.
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_exclude_unidentified"
+    )]
+    pub exclude_unidentified: Option<bool>,
+    /// Include unidentified commits, even if excluded in the configuration file.
+    #[arg(long, overrides_with = "exclude_unidentified")]
+    pub no_exclude_unidentified: bool,
     /// Don't include commits which are not attached to a pull request.
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_exclude_not_pr"
+    )]
+    pub exclude_not_pr: Option<bool>,
+    /// Include commits which are not attached to a pull request, even if excluded in the configuration file.
+    #[arg(long, overrides_with = "exclude_not_pr")]
+    pub no_exclude_not_pr: bool,
+    /// Only include the commits with one of these scopes. Example: 'api,cli'.
+    #[arg(long, value_delimiter = ',')]
+    pub include_scope: Vec<String>,
+    /// Don't include the commits with one of these scopes. Example: 'ci,deps'.
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_scope: Vec<String>,
+    #[command(flatten)]
+    pub ignore: IgnoreRules,
+    /// How to highlight breaking changes. Default to section.
+    #[arg(long)]
+    pub breaking: Option<BreakingChanges>,
+    /// Name of the breaking changes section. Default to "Breaking Changes".
+    #[arg(long)]
+    pub breaking_section: Option<String>,
+    /// We use the Github api to map commit sha to PRs. Default to github.
     #[arg(long)]
-    pub exclude_not_pr: bool,
-    /// We use the Github api to map commit sha to PRs.
-    #[arg(long, default_value_t)]
-    pub provider: GitProvider,
+    pub provider: Option<GitProvider>,
+    /// Base url of the git provider instance, for self-hosted instances. Example: 'https://gitlab.example.com'.
+    #[arg(long, value_hint = ValueHint::Url)]
+    pub provider_url: Option<String>,
     /// Needed for fetching PRs. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
     #[arg(long)]
     pub repo: Option<String>,
     /// Omit the PR link from the output.
-    #[arg(long)]
-    pub omit_pr_link: bool,
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_omit_pr_link"
+    )]
+    pub omit_pr_link: Option<bool>,
+    /// Include the PR link, even if omitted in the configuration file.
+    #[arg(long, overrides_with = "omit_pr_link")]
+    pub no_omit_pr_link: bool,
     /// Omit contributors' acknowledgements/mention.
-    #[arg(long)]
-    pub omit_thanks: bool,
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_omit_thanks"
+    )]
+    pub omit_thanks: Option<bool>,
+    /// Include contributors' acknowledgements, even if omitted in the configuration file.
+    #[arg(long, overrides_with = "omit_thanks")]
+    pub no_omit_thanks: bool,
     /// Print the result on the standard output.
     #[arg(long)]
     pub stdout: bool,
@@ -210,16 +506,30 @@ pub struct Generate {
     /// Include all commits in \"since..until\".
     #[arg(long, requires = "since")]
     pub until: Option<String>,
+    /// Prefix of the version tags. Example: 'v', or 'mycrate-v'. By default, 'v' is used if most tags start with it.
+    #[arg(long)]
+    pub tag_prefix: Option<String>,
+    /// Only include the commits touching this path, relative to the root of the repo.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+    /// Use the changelog, path and tag prefix of this package, defined in the configuration file.
+    #[arg(long)]
+    pub package: Option<String>,
+    /// Generate the changelog of every crate of the Cargo workspace, with '{name}-v' tags. The workspace must be at the root of the repo.
+    #[arg(
+        long,
+        conflicts_with_all = ["package", "path", "file", "specific", "milestone", "since", "until"],
+    )]
+    pub workspace: bool,
 }
 
 /// Generate a new release. By default, use the last tag present in the repo.
 #[derive(Debug, Clone, Args)]
 pub struct Release {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
     )]
     pub file: Option<PathBuf>,
@@ -231,40 +541,126 @@ pub struct Release {
         default_missing_value=None
     )]
     pub version: Option<Version>,
+    /// Compute the version number from the changes since the last release.
+    #[arg(long, conflicts_with = "version")]
+    pub bump: Option<BumpKind>,
+    /// Make a pre-release with this identifier. Example: with 'beta', 1.2.0-beta.1, then 1.2.0-beta.2, ... If the last release is a pre-release, continue its series.
+    #[arg(long)]
+    pub pre: Option<String>,
+    /// Path to the commit type to changelog section map. Used with --bump.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub map: Option<PathBuf>,
+    /// Section map defined inline in the configuration file.
+    #[arg(skip)]
+    pub map_inline: Option<MapMessageToSection>,
+    /// Name of the breaking changes section. Used with --bump. Default to "Breaking Changes".
+    #[arg(long)]
+    pub breaking_section: Option<String>,
     /// Previous version number. Used for the diff.
     #[arg(long)]
     pub previous_version: Option<Version>,
-    /// We use the Github link to produce the tags diff.
-    #[arg(long, default_value_t)]
-    pub provider: GitProvider,
+    /// Date of the release. Default to the date of the tag, or today. Example: '2024-05-01'.
+    #[arg(long)]
+    pub date: Option<NaiveDate>,
+    /// We use the Github link to produce the tags diff. Default to github.
+    #[arg(long)]
+    pub provider: Option<GitProvider>,
+    /// Base url of the git provider instance, for self-hosted instances. Example: 'https://gitlab.example.com'.
+    #[arg(long, value_hint = ValueHint::Url)]
+    pub provider_url: Option<String>,
     /// Needed for the tags diff PRs. Example: 'wiiznokes/changen'. Already defined for you in Github Actions.
     #[arg(long)]
     pub repo: Option<String>,
     /// Omit the commit history between releases.
-    #[arg(long)]
-    pub omit_diff: bool,
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_omit_diff"
+    )]
+    pub omit_diff: Option<bool>,
+    /// Include the commit history between releases, even if omitted in the configuration file.
+    #[arg(long, overrides_with = "omit_diff")]
+    pub no_omit_diff: bool,
     /// Override the release with the same version if it exist, by replacing all the existing release notes.
     #[arg(long)]
     pub force: bool,
-    /// Add this text as a header of the release. If a header already exist, it will be inserted before the existing one.
+    /// Add this text as a header of the release. If a header already exist, it will be inserted before the existing one. {VERSION} will be replaced by the version of the release.
     #[arg(long)]
     pub header: Option<String>,
-    /// Merge older dev version into this new release
-    #[arg(long, default_value_t)]
-    pub merge_dev_versions: MergeDevVersions,
+    /// Merge older dev version into this new release. Default to auto.
+    #[arg(long)]
+    pub merge_dev_versions: Option<MergeDevVersions>,
+    /// Templates defined in the configuration file.
+    #[arg(skip)]
+    pub templates: Templates,
+    /// Prefix of the version tags. Example: 'v', or 'mycrate-v'. By default, 'v' is used if most tags start with it.
+    #[arg(long)]
+    pub tag_prefix: Option<String>,
+    /// Only include the commits touching this path when computing the version, relative to the root of the repo.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+    /// Use the changelog, path and tag prefix of this package, defined in the configuration file.
+    #[arg(long)]
+    pub package: Option<String>,
+    #[command(flatten)]
+    pub ignore: IgnoreRules,
+    /// Release every crate of the Cargo workspace whose version in Cargo.toml is not in its changelog. Releases of the other crates of the workspace are mentioned in the dependents. The workspace must be at the root of the repo.
+    #[arg(
+        long,
+        conflicts_with_all = ["package", "path", "file", "version", "bump", "pre", "previous_version"],
+    )]
+    pub workspace: bool,
     /// Print the result on the standard output.
     #[arg(long)]
     pub stdout: bool,
 }
 
+/// Print the next version, computed from the Unreleased section, or the commits since the last release.
+#[derive(Debug, Clone, Args)]
+pub struct Bump {
+    /// How to bump the version.
+    #[arg(default_value_t)]
+    pub kind: BumpKind,
+    /// Compute a pre-release with this identifier. Example: 'beta'.
+    #[arg(long)]
+    pub pre: Option<String>,
+    /// Path to the changelog file. Default to CHANGELOG.md.
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Path to the commit type to changelog section map.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub map: Option<PathBuf>,
+    /// Section map defined inline in the configuration file.
+    #[arg(skip)]
+    pub map_inline: Option<MapMessageToSection>,
+    /// Name of the breaking changes section. Default to "Breaking Changes".
+    #[arg(long)]
+    pub breaking_section: Option<String>,
+    /// Prefix of the version tags. Example: 'v', or 'mycrate-v'. By default, 'v' is used if most tags start with it.
+    #[arg(long)]
+    pub tag_prefix: Option<String>,
+    /// Only include the commits touching this path, relative to the root of the repo.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub path: Option<PathBuf>,
+    /// Use the changelog, path and tag prefix of this package, defined in the configuration file.
+    #[arg(long)]
+    pub package: Option<String>,
+    #[command(flatten)]
+    pub ignore: IgnoreRules,
+}
+
 /// Validate a changelog syntax
 #[derive(Debug, Clone, Args)]
 pub struct Validate {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
     )]
     pub file: Option<PathBuf>,
@@ -274,6 +670,12 @@ pub struct Validate {
     /// Path to the commit type to changelog section map.
     #[arg(long, value_hint = ValueHint::FilePath)]
     pub map: Option<PathBuf>,
+    /// Section map defined inline in the configuration file.
+    #[arg(skip)]
+    pub map_inline: Option<MapMessageToSection>,
+    /// Name of the breaking changes section, placed first. Default to "Breaking Changes".
+    #[arg(long)]
+    pub breaking_section: Option<String>,
     /// Show the Abstract Syntax Tree.
     #[arg(long)]
     pub ast: bool,
@@ -285,11 +687,10 @@ pub struct Validate {
 /// Show a releases on stdout. By default, show the last release.
 #[derive(Debug, Clone, Args)]
 pub struct Show {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
     )]
     pub file: Option<PathBuf>,
@@ -309,15 +710,17 @@ pub struct Show {
         default_missing_value=None
     )]
     pub version: Option<Regex>,
+    /// Templates defined in the configuration file.
+    #[arg(skip)]
+    pub templates: Templates,
 }
 /// Create a new changelog file with an accepted syntax
 #[derive(Debug, Clone, Args)]
 pub struct New {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
     )]
     pub path: Option<PathBuf>,
@@ -329,11 +732,10 @@ pub struct New {
 /// Remove a release
 #[derive(Debug, Clone, Args)]
 pub struct Remove {
-    /// Path to the changelog file.
+    /// Path to the changelog file. Default to CHANGELOG.md.
     #[arg(
         short,
         long,
-        default_value = "CHANGELOG.md",
         value_hint = ValueHint::FilePath,
     )]
     pub file: Option<PathBuf>,
@@ -356,3 +758,112 @@ pub struct RemoveSelection {
     #[arg(short, long)]
     pub version: Option<Regex>,
 }
+
+/// Mark a release as yanked
+#[derive(Debug, Clone, Args)]
+pub struct Yank {
+    /// Path to the changelog file. Default to CHANGELOG.md.
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+    )]
+    pub file: Option<PathBuf>,
+    /// Print the result on the standard output.
+    #[arg(long)]
+    pub stdout: bool,
+    /// Version of the release to yank.
+    #[arg(short, long, required_unless_present = "index")]
+    pub version: Option<String>,
+    /// Remove the yanked mark instead.
+    #[arg(long)]
+    pub undo: bool,
+    /// Crates.io-style index file of the package, with one JSON object per line. Without --version, the yanked state of every release found in the index is copied. With --version, the index must agree.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub index: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn section(section: &str) -> Option<MapTarget> {
+        Some(MapTarget::Section(section.into()))
+    }
+
+    #[test]
+    fn legacy_map() {
+        let map = MapMessageToSection::default();
+
+        assert_eq!(map.map_commit("fix", None), section("Fixed"));
+        assert_eq!(
+            map.map_commit("chore", Some("deps")),
+            section("Dependencies")
+        );
+        assert_eq!(map.fallback(), DEFAULT_FALLBACK_SECTION);
+
+        // only whole words
+        assert_eq!(
+            map.try_find_section(("Fix the parser", "")),
+            section("Fixed")
+        );
+        assert_eq!(map.try_find_section(("prefix the address", "")), None);
+    }
+
+    #[test]
+    fn rich_map() {
+        let map: MapMessageToSection = toml::from_str(
+            r#"
+fallback = "Other"
+
+[[rule]]
+section = "Fixed"
+types = ["fix"]
+words = ["bug"]
+body = ['(?i)^fixes #\d+']
+
+[[rule]]
+section = "Security"
+title = ['(?i)\bCVE-\d+']
+priority = 10
+
+[[rule]]
+ignore = true
+types = ["*(ci)"]
+words = ["typo"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(map.sections(), ["Fixed", "Security"]);
+        assert_eq!(map.fallback(), "Other");
+
+        assert_eq!(map.map_commit("fix", None), section("Fixed"));
+        assert_eq!(map.map_commit("fix", Some("ci")), Some(MapTarget::Ignore));
+
+        assert_eq!(
+            map.try_find_section(("a bug with CVE-2024-1", "")),
+            section("Security")
+        );
+        assert_eq!(
+            map.try_find_section(("update the parser", "Fixes #12")),
+            section("Fixed")
+        );
+        // body rules are not matched on the title
+        assert_eq!(map.try_find_section(("Fixes #12", "")), None);
+        assert_eq!(
+            map.try_find_section(("typo in the readme", "")),
+            Some(MapTarget::Ignore)
+        );
+
+        toml::from_str::<MapMessageToSection>(
+            r#"
+[[rule]]
+types = ["fix"]
+"#,
+        )
+        .unwrap_err();
+    }
+}
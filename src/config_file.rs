@@ -0,0 +1,614 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail};
+use changelog::CalVerFormat;
+use serde::Deserialize;
+
+use crate::{
+    config::{
+        BreakingChanges, Bump, CommitMessageParsing, Generate, MapMessageToSection,
+        MergeDevVersions, New, Release, Remove, Show, Validate, Yank,
+    },
+    git_provider::GitProvider,
+    ignore::IgnoreRules,
+    template::Templates,
+};
+
+pub const CONFIG_FILE_NAME: &str = "changen.toml";
+
+/// Either a path to a json file, or the map itself.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MapConfig {
+    Path(PathBuf),
+    Inline(MapMessageToSection),
+}
+
+/// A package of a monorepo, with its own changelog and tags.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PackageConfig {
+    pub name: String,
+    /// Relative to the root of the repo.
+    pub path: PathBuf,
+    /// Default to `{path}/CHANGELOG.md`.
+    pub file: Option<PathBuf>,
+    /// Default to `{name}-v`.
+    pub tag_prefix: Option<String>,
+}
+
+impl PackageConfig {
+    pub fn tag_prefix(&self) -> String {
+        self.tag_prefix
+            .clone()
+            .unwrap_or_else(|| format!("{}-v", self.name))
+    }
+}
+
+/// Content of `changen.toml`, or of the `[package.metadata.changen]` section of Cargo.toml.
+///
+/// Values passed on the command line always take precedence.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ConfigFile {
+    pub file: Option<PathBuf>,
+    pub provider: Option<GitProvider>,
+    pub provider_url: Option<String>,
+    pub repo: Option<String>,
+    pub map: Option<MapConfig>,
+    pub parsing: Option<CommitMessageParsing>,
+    pub exclude_unidentified: Option<bool>,
+    pub exclude_not_pr: Option<bool>,
+    pub include_scope: Option<Vec<String>>,
+    pub exclude_scope: Option<Vec<String>>,
+    pub ignore: Option<IgnoreRules>,
+    pub breaking: Option<BreakingChanges>,
+    pub breaking_section: Option<String>,
+    pub omit_pr_link: Option<bool>,
+    pub omit_thanks: Option<bool>,
+    pub omit_diff: Option<bool>,
+    /// `{VERSION}` will be replaced by the version of the release.
+    pub header: Option<String>,
+    pub merge_dev_versions: Option<MergeDevVersions>,
+    pub templates: Option<Templates>,
+    pub calver: Option<CalVerFormat>,
+    pub tag_prefix: Option<String>,
+    #[serde(rename = "package")]
+    pub packages: Vec<PackageConfig>,
+}
+
+impl ConfigFile {
+    /// Load the config from `path`, or search it from the current directory to the root of the repo.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Option<ConfigFile>> {
+        let (path, config) = match path {
+            Some(path) => match read_config(path)? {
+                Some(config) => (path.to_path_buf(), config),
+                None => bail!("No changen configuration found in {}", path.display()),
+            },
+            None => match find_config(&env::current_dir()?)? {
+                Some(res) => res,
+                None => return Ok(None),
+            },
+        };
+
+        info!("use config file: {}", path.display());
+
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        Ok(Some(config.resolve_paths(base)))
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<ConfigFile> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Paths in the config are relative to its directory.
+    fn resolve_paths(mut self, base: &Path) -> ConfigFile {
+        if let Some(file) = &mut self.file {
+            *file = base.join(&file);
+        }
+        if let Some(MapConfig::Path(map)) = &mut self.map {
+            *map = base.join(&map);
+        }
+        for package in &mut self.packages {
+            let file = match &package.file {
+                Some(file) => file.clone(),
+                None => package.path.join("CHANGELOG.md"),
+            };
+            package.file = Some(base.join(file));
+        }
+        self
+    }
+
+    /// The map passed on the command line replace the one of the config.
+    fn apply_map(&self, map: &mut Option<PathBuf>, map_inline: &mut Option<MapMessageToSection>) {
+        if map.is_none() {
+            match &self.map {
+                Some(MapConfig::Path(path)) => *map = Some(path.clone()),
+                Some(MapConfig::Inline(inline)) => *map_inline = Some(inline.clone()),
+                None => {}
+            }
+        }
+    }
+
+    /// Add the packages which are not already defined.
+    pub fn with_packages<I>(mut self, packages: I) -> Self
+    where
+        I: IntoIterator<Item = PackageConfig>,
+    {
+        for package in packages {
+            if self.package(&package.name).is_err() {
+                self.packages.push(package);
+            }
+        }
+        self
+    }
+
+    pub fn package(&self, name: &str) -> anyhow::Result<&PackageConfig> {
+        match self.packages.iter().find(|package| package.name == name) {
+            Some(package) => Ok(package),
+            None => bail!("No package named {name} in the configuration file"),
+        }
+    }
+
+    /// The settings of the selected package take precedence over the global ones.
+    /// Return the path of the package.
+    fn apply_package(
+        &self,
+        package: Option<&str>,
+        file: &mut Option<PathBuf>,
+        tag_prefix: &mut Option<String>,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let Some(package) = package else {
+            return Ok(None);
+        };
+
+        let package = self.package(package)?;
+
+        *file = file.take().or(package.file.clone());
+        *tag_prefix = tag_prefix.take().or(Some(package.tag_prefix()));
+
+        Ok(Some(package.path.clone()))
+    }
+
+    pub fn apply_generate(&self, options: &mut Generate) -> anyhow::Result<()> {
+        let path = self.apply_package(
+            options.package.as_deref(),
+            &mut options.file,
+            &mut options.tag_prefix,
+        )?;
+        options.path = options.path.take().or(path);
+
+        options.file = options.file.take().or(self.file.clone());
+        options.parsing = options.parsing.take().or(self.parsing.clone());
+        options.provider = options.provider.take().or(self.provider.clone());
+        options.provider_url = options.provider_url.take().or(self.provider_url.clone());
+        options.repo = options.repo.take().or(self.repo.clone());
+        options.exclude_unidentified = options
+            .exclude_unidentified
+            .take()
+            .or(options.no_exclude_unidentified.then_some(false))
+            .or(self.exclude_unidentified);
+        options.exclude_not_pr = options
+            .exclude_not_pr
+            .take()
+            .or(options.no_exclude_not_pr.then_some(false))
+            .or(self.exclude_not_pr);
+        if options.include_scope.is_empty() {
+            options.include_scope = self.include_scope.clone().unwrap_or_default();
+        }
+        if options.exclude_scope.is_empty() {
+            options.exclude_scope = self.exclude_scope.clone().unwrap_or_default();
+        }
+        if let Some(ignore) = &self.ignore {
+            options.ignore.merge(ignore);
+        }
+        options.breaking = options.breaking.take().or(self.breaking.clone());
+        if let Some(templates) = &self.templates {
+            options.templates.merge(templates);
+        }
+        options.breaking_section = options
+            .breaking_section
+            .take()
+            .or(self.breaking_section.clone());
+        options.omit_pr_link = options
+            .omit_pr_link
+            .take()
+            .or(options.no_omit_pr_link.then_some(false))
+            .or(self.omit_pr_link);
+        options.omit_thanks = options
+            .omit_thanks
+            .take()
+            .or(options.no_omit_thanks.then_some(false))
+            .or(self.omit_thanks);
+        options.tag_prefix = options.tag_prefix.take().or(self.tag_prefix.clone());
+
+        self.apply_map(&mut options.map, &mut options.map_inline);
+
+        Ok(())
+    }
+
+    pub fn apply_release(&self, options: &mut Release) -> anyhow::Result<()> {
+        let path = self.apply_package(
+            options.package.as_deref(),
+            &mut options.file,
+            &mut options.tag_prefix,
+        )?;
+        options.path = options.path.take().or(path);
+
+        options.file = options.file.take().or(self.file.clone());
+        if let Some(ignore) = &self.ignore {
+            options.ignore.merge(ignore);
+        }
+        options.provider = options.provider.take().or(self.provider.clone());
+        options.provider_url = options.provider_url.take().or(self.provider_url.clone());
+        options.repo = options.repo.take().or(self.repo.clone());
+        options.omit_diff = options
+            .omit_diff
+            .take()
+            .or(options.no_omit_diff.then_some(false))
+            .or(self.omit_diff);
+        options.header = options.header.take().or(self.header.clone());
+        options.merge_dev_versions = options
+            .merge_dev_versions
+            .take()
+            .or(self.merge_dev_versions.clone());
+        if let Some(templates) = &self.templates {
+            options.templates.merge(templates);
+        }
+        options.breaking_section = options
+            .breaking_section
+            .take()
+            .or(self.breaking_section.clone());
+        options.tag_prefix = options.tag_prefix.take().or(self.tag_prefix.clone());
+
+        self.apply_map(&mut options.map, &mut options.map_inline);
+
+        Ok(())
+    }
+
+    pub fn apply_bump(&self, options: &mut Bump) -> anyhow::Result<()> {
+        let path = self.apply_package(
+            options.package.as_deref(),
+            &mut options.file,
+            &mut options.tag_prefix,
+        )?;
+        options.path = options.path.take().or(path);
+
+        options.file = options.file.take().or(self.file.clone());
+        if let Some(ignore) = &self.ignore {
+            options.ignore.merge(ignore);
+        }
+        options.breaking_section = options
+            .breaking_section
+            .take()
+            .or(self.breaking_section.clone());
+        options.tag_prefix = options.tag_prefix.take().or(self.tag_prefix.clone());
+
+        self.apply_map(&mut options.map, &mut options.map_inline);
+
+        Ok(())
+    }
+
+    pub fn apply_validate(&self, options: &mut Validate) {
+        options.file = options.file.take().or(self.file.clone());
+        options.breaking_section = options
+            .breaking_section
+            .take()
+            .or(self.breaking_section.clone());
+
+        self.apply_map(&mut options.map, &mut options.map_inline);
+    }
+
+    pub fn apply_show(&self, options: &mut Show) {
+        options.file = options.file.take().or(self.file.clone());
+        if let Some(templates) = &self.templates {
+            options.templates.merge(templates);
+        }
+    }
+
+    pub fn apply_new(&self, options: &mut New) {
+        options.path = options.path.take().or(self.file.clone());
+    }
+
+    pub fn apply_remove(&self, options: &mut Remove) {
+        options.file = options.file.take().or(self.file.clone());
+    }
+
+    pub fn apply_yank(&self, options: &mut Yank) {
+        options.file = options.file.take().or(self.file.clone());
+    }
+}
+
+/// Read a `changen.toml`, or the changen section of a Cargo.toml.
+fn read_config(path: &Path) -> anyhow::Result<Option<ConfigFile>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Can't read config file {}: {e}", path.display()))?;
+
+    if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+        cargo_metadata_config(&content)
+    } else {
+        ConfigFile::parse(&content).map(Some)
+    }
+}
+
+fn cargo_metadata_config(content: &str) -> anyhow::Result<Option<ConfigFile>> {
+    let manifest: toml::Table = toml::from_str(content)?;
+
+    for key in ["package", "workspace"] {
+        if let Some(config) = manifest
+            .get(key)
+            .and_then(|e| e.get("metadata"))
+            .and_then(|e| e.get("changen"))
+        {
+            return Ok(Some(config.clone().try_into()?));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Walk up from `dir` until the root of the repo.
+fn find_config(dir: &Path) -> anyhow::Result<Option<(PathBuf, ConfigFile)>> {
+    for dir in dir.ancestors() {
+        let path = dir.join(CONFIG_FILE_NAME);
+        if path.is_file() {
+            if let Some(config) = read_config(&path)? {
+                return Ok(Some((path, config)));
+            }
+        }
+
+        let path = dir.join("Cargo.toml");
+        if path.is_file() {
+            if let Some(config) = read_config(&path)? {
+                return Ok(Some((path, config)));
+            }
+        }
+
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+    use pretty_assertions::assert_eq;
+
+    use crate::config::{Cli, Commands};
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = ConfigFile::parse(
+            r#"
+file = "docs/CHANGELOG.md"
+provider = "forgejo"
+provider-url = "https://codeberg.org"
+repo = "wiiznokes/changen"
+parsing = "strict"
+exclude-not-pr = true
+header = "Release {VERSION}"
+merge-dev-versions = "no"
+
+[map]
+Fixed = ["fix"]
+Added = ["feat"]
+Changed = ["refactor", "perf"]
+
+[templates]
+note = "{{ message }} ({{ pr }})"
+show-section = "🐛 {{ title }}"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.provider, Some(GitProvider::Gitea));
+        assert_eq!(
+            config.templates.as_ref().unwrap().note.as_deref(),
+            Some("{{ message }} ({{ pr }})")
+        );
+        assert_eq!(
+            config.templates.as_ref().unwrap().show_section.as_deref(),
+            Some("🐛 {{ title }}")
+        );
+        assert_eq!(config.parsing, Some(CommitMessageParsing::Strict));
+        assert_eq!(config.merge_dev_versions, Some(MergeDevVersions::No));
+        assert_eq!(config.exclude_not_pr, Some(true));
+        assert_eq!(config.exclude_unidentified, None);
+
+        let Some(MapConfig::Inline(map)) = &config.map else {
+            panic!("inline map expected");
+        };
+        assert_eq!(map.sections(), vec!["Fixed", "Added", "Changed"]);
+
+        let config = config.resolve_paths(Path::new("/repo"));
+        assert_eq!(config.file, Some(PathBuf::from("/repo/docs/CHANGELOG.md")));
+    }
+
+    #[test]
+    fn cargo_metadata() {
+        let config = cargo_metadata_config(
+            r#"
+[package]
+name = "test"
+
+[package.metadata.changen]
+map = "map.json"
+omit-thanks = true
+"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(matches!(config.map, Some(MapConfig::Path(_))));
+        assert_eq!(config.omit_thanks, Some(true));
+
+        let config = cargo_metadata_config(
+            r#"
+[package]
+name = "test"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.is_none());
+    }
+
+    #[test]
+    fn cli_override() {
+        let config = ConfigFile::parse(
+            r#"
+file = "CHANGES.md"
+provider = "gitlab"
+map = "map.json"
+omit-thanks = true
+exclude-not-pr = true
+
+[templates]
+note = "{{ message }}"
+footer = "{{ link }}"
+"#,
+        )
+        .unwrap();
+
+        let mut options = Generate {
+            provider: Some(GitProvider::None),
+            omit_thanks: None,
+            no_omit_thanks: true,
+            templates: Templates {
+                note: Some("{{ message }} ({{ pr }})".into()),
+                ..Default::default()
+            },
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+
+        config.apply_generate(&mut options).unwrap();
+
+        assert_eq!(options.file, Some(PathBuf::from("CHANGES.md")));
+        assert_eq!(options.provider, Some(GitProvider::None));
+        assert_eq!(options.map, Some(PathBuf::from("map.json")));
+        assert_eq!(options.omit_thanks, Some(false));
+        assert_eq!(options.exclude_not_pr, Some(true));
+        assert_eq!(
+            options.templates.note.as_deref(),
+            Some("{{ message }} ({{ pr }})")
+        );
+        assert_eq!(options.templates.footer.as_deref(), Some("{{ link }}"));
+
+        let Commands::Generate(options) = Cli::parse_from([
+            "changen",
+            "generate",
+            "--omit-thanks",
+            "--no-omit-thanks",
+            "--exclude-not-pr",
+        ])
+        .command
+        else {
+            panic!("generate expected");
+        };
+
+        assert_eq!(options.omit_thanks, None);
+        assert!(options.no_omit_thanks);
+        assert_eq!(options.exclude_not_pr, Some(true));
+        assert_eq!(options.omit_pr_link, None);
+    }
+
+    #[test]
+    fn package() {
+        let config = ConfigFile::parse(
+            r#"
+file = "CHANGELOG.md"
+
+[[package]]
+name = "changelog_document"
+path = "changelog_document"
+
+[[package]]
+name = "changen"
+path = "src"
+file = "CHANGELOG.md"
+tag-prefix = "v"
+"#,
+        )
+        .unwrap()
+        .resolve_paths(Path::new(""));
+
+        let mut options = Generate {
+            package: Some("changelog_document".into()),
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+        config.apply_generate(&mut options).unwrap();
+
+        assert_eq!(
+            options.file,
+            Some(PathBuf::from("changelog_document/CHANGELOG.md"))
+        );
+        assert_eq!(options.path, Some(PathBuf::from("changelog_document")));
+        assert_eq!(options.tag_prefix, Some("changelog_document-v".into()));
+
+        let mut options = Generate {
+            package: Some("changen".into()),
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+        config.apply_generate(&mut options).unwrap();
+
+        assert_eq!(options.file, Some(PathBuf::from("CHANGELOG.md")));
+        assert_eq!(options.tag_prefix, Some("v".into()));
+
+        let mut options = Generate {
+            package: Some("unknown".into()),
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+        config.apply_generate(&mut options).unwrap_err();
+    }
+
+    #[test]
+    fn ignore() {
+        let config = ConfigFile::parse(
+            r#"
+[ignore]
+author = ["dependabot", "renovate"]
+merge-commits = true
+reverts = false
+paths = ["docs"]
+"#,
+        )
+        .unwrap();
+
+        let mut options = Generate {
+            ignore: IgnoreRules {
+                author: vec!["github-actions".into()],
+                ..Default::default()
+            },
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+        config.apply_generate(&mut options).unwrap();
+
+        assert_eq!(
+            options.ignore.author,
+            vec!["github-actions", "dependabot", "renovate"]
+        );
+        assert!(options.ignore.merge_commits);
+        assert_eq!(options.ignore.reverts, Some(false));
+        assert_eq!(options.ignore.paths, vec![PathBuf::from("docs")]);
+
+        // the command line wins
+        let mut options = Generate {
+            ignore: IgnoreRules {
+                reverts: Some(true),
+                ..Default::default()
+            },
+            ..crate::integration_test::DEFAULT_GENERATE.clone()
+        };
+        config.apply_generate(&mut options).unwrap();
+        assert_eq!(options.ignore.reverts, Some(true));
+    }
+}
@@ -1,31 +1,46 @@
+use std::sync::LazyLock;
+
 use crate::{
-    commit_parser::{parse_commit, FormattedCommit},
+    commit_parser::{parse_body, parse_commit, FormattedCommit},
     config::Generate,
-    git_provider::RelatedPr,
-    repository::{Period, RawCommit, Repository},
+    git_provider::{Provider, RelatedPr},
+    repository::{CommitFilter, Period, RawCommit, Repository, TagFormat},
+    revert::{reverted, Reverted},
+    template::{render, today, NoteContext},
 };
 use anyhow::{bail, Result};
 use changelog::{
     ser::{serialize_changelog, serialize_release_section_note},
-    ChangeLog, Release, ReleaseSection, ReleaseSectionNote,
+    CalVerFormat, ChangeLog, NoteSource, Release, ReleaseSection, ReleaseSectionNote,
 };
+use regex::Regex;
 
-use crate::config::{CommitMessageParsing, MapMessageToSection};
+use crate::config::{
+    BreakingChanges, CommitMessageParsing, MapMessageToSection, MapTarget, DEFAULT_BREAKING_SECTION,
+};
 
 pub fn generate<R: Repository>(
     r: &R,
+    p: &dyn Provider,
     mut changelog: ChangeLog,
     options: &Generate,
+    calver: Option<&CalVerFormat>,
 ) -> Result<String> {
-    let map = MapMessageToSection::try_new(options.map.as_ref())?;
+    let map = MapMessageToSection::try_new(options.map.as_ref(), options.map_inline.as_ref())?;
 
     let changelog_cloned = changelog.clone();
 
     let unreleased = changelog.unreleased_or_default();
 
-    gen_release_notes::<R>(r, &changelog_cloned, unreleased, &map, options)?;
+    let r = &CommitFilter::new(r, options.path.clone(), &options.ignore)?;
 
-    changelog.sanitize(&map.to_fmt_options());
+    gen_release_notes(r, p, &changelog_cloned, unreleased, &map, options, calver)?;
+
+    let breaking_section = options
+        .breaking_section
+        .as_deref()
+        .unwrap_or(DEFAULT_BREAKING_SECTION);
+    changelog.sanitize(&map.to_fmt_options(breaking_section));
 
     let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
 
@@ -33,41 +48,47 @@ pub fn generate<R: Repository>(
 }
 
 fn gen_release_notes<R: Repository>(
-    r: &R,
+    r: &CommitFilter<'_, R>,
+    p: &dyn Provider,
     changelog: &ChangeLog,
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
+    calver: Option<&CalVerFormat>,
 ) -> Result<()> {
     if let Some(specific) = &options.specific {
-        return handle_specific::<R>(r, unreleased, map, options, specific);
+        return handle_specific(r, p, unreleased, map, options, specific);
     }
 
     if let Some(milestone) = &options.milestone {
-        return handle_milestone(unreleased, map, options, milestone);
+        return handle_milestone(p, unreleased, map, options, milestone);
     }
 
-    handle_period::<R>(r, changelog, unreleased, map, options)
+    handle_period(r, p, changelog, unreleased, map, options, calver)
 }
 
 fn handle_milestone(
+    p: &dyn Provider,
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
     milestone: &str,
 ) -> Result<()> {
-    for pr in options
-        .provider
-        .milestone_prs(&options.repo.clone().unwrap(), milestone)?
-    {
+    for pr in p.milestone_prs(&options.repo.clone().unwrap(), milestone)? {
         let raw_commit = RawCommit {
             title: pr.title.clone().unwrap_or_default(),
             body: pr.body.clone().unwrap_or_default(),
-            sha: "".into(),
+            sha: pr.merge_commit.clone().unwrap_or_default(),
             list_files: vec![],
+            parents: vec![],
             author: pr.author.clone().unwrap_or_default(),
         };
 
+        if unreleased.has_note_from(&raw_commit.sha) || unreleased.has_note_from_pr(&pr.pr_id) {
+            debug!("pr {} is already in the changelog", pr.pr_id);
+            continue;
+        }
+
         match get_release_note(&raw_commit, Some(&pr), map, options) {
             Ok((section_title, release_note)) => {
                 insert_release_note(unreleased, section_title, release_note);
@@ -81,15 +102,24 @@ fn handle_milestone(
 
 fn handle_specific<R: Repository>(
     r: &R,
+    p: &dyn Provider,
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
     specific: &str,
 ) -> Result<()> {
-    let raw_commit = RawCommit::from_sha(r, specific);
+    let raw_commit = r.commit(specific)?;
+
+    if unreleased.has_note_from(&raw_commit.sha) {
+        eprintln!(
+            "commit {}: already in the changelog.",
+            raw_commit.short_commit()
+        );
+        return Ok(());
+    }
 
     let related_pr = match &options.repo {
-        Some(repo) => match options.provider.related_pr(repo, &raw_commit.sha) {
+        Some(repo) => match p.related_pr(repo, &raw_commit.sha) {
             Ok(related_pr) => Some(related_pr),
             Err(e) => {
                 eprintln!("error while requesting pr link: {}", e);
@@ -115,16 +145,21 @@ fn handle_specific<R: Repository>(
 }
 
 fn handle_period<R: Repository>(
-    r: &R,
+    r: &CommitFilter<'_, R>,
+    p: &dyn Provider,
     changelog: &ChangeLog,
     unreleased: &mut Release,
     map: &MapMessageToSection,
     options: &Generate,
+    calver: Option<&CalVerFormat>,
 ) -> Result<()> {
-    let since = options
-        .since
-        .clone()
-        .or_else(|| changelog.last_version().map(|e| e.to_string()));
+    let since = match (&options.since, changelog.last_version()) {
+        (Some(since), _) => Some(since.clone()),
+        (None, Some(last_version)) => {
+            Some(TagFormat::new(r, options.tag_prefix.as_deref(), calver)?.tag(&last_version))
+        }
+        (None, None) => None,
+    };
 
     let period = Period {
         since,
@@ -133,10 +168,20 @@ fn handle_period<R: Repository>(
 
     info!("generate period: {:?}", period);
 
-    let commits = r.commits_between_tags(&period);
+    let (commits, cancelled) = r.commits_and_reverted(&period)?;
+
+    // the reverted commit was added by a previous run, and its revert is in the period
+    for reverted in &cancelled {
+        if remove_note_from(unreleased, &reverted.sha) {
+            eprintln!(
+                "commit {}: reverted, its note was removed.",
+                reverted.short_commit()
+            );
+        }
+    }
 
     let mut last_prs = match &options.repo {
-        Some(repo) => match options.provider.last_prs(repo, commits.len()) {
+        Some(repo) => match p.last_prs_by_merge_commit(repo, commits.len()) {
             Ok(last_prs) => Some(last_prs),
             Err(e) => {
                 eprintln!("error while requesting pr link: {}", e);
@@ -146,11 +191,29 @@ fn handle_period<R: Repository>(
         None => None,
     };
 
-    for sha in commits {
-        let raw_commit = RawCommit::from_sha::<R>(r, &sha);
+    for raw_commit in commits {
+        // added by a previous run
+        if unreleased.has_note_from(&raw_commit.sha) {
+            debug!(
+                "commit {} is already in the changelog",
+                raw_commit.short_commit()
+            );
+            continue;
+        }
+
+        // the reverted commit was added by a previous run
+        if let Some(reverted) = reverted(&raw_commit).filter(|_| r.ignore.reverts()) {
+            if remove_reverted_note(r, unreleased, map, options, &reverted) {
+                eprintln!(
+                    "commit {}: the note of the reverted commit was removed.",
+                    raw_commit.short_commit()
+                );
+                continue;
+            }
+        }
 
         let related_pr = match last_prs {
-            Some(ref mut last_prs) => last_prs.remove(&sha),
+            Some(ref mut last_prs) => last_prs.remove(&raw_commit.sha),
             None => None,
         };
 
@@ -158,7 +221,7 @@ fn handle_period<R: Repository>(
         let related_pr = match related_pr {
             Some(related_pr) => Some(related_pr),
             None => match &options.repo {
-                Some(repo) => options.provider.offline_related_pr(repo, &raw_commit),
+                Some(repo) => p.offline_related_pr(repo, &raw_commit),
                 None => None,
             },
         };
@@ -174,6 +237,79 @@ fn handle_period<R: Repository>(
     Ok(())
 }
 
+/// Remove the note of the reverted commit from `unreleased`.
+/// Without a hidden marker, the note is found by its message.
+/// The PR link and the thanks added after the message are not compared.
+fn remove_reverted_note<R: Repository>(
+    r: &R,
+    unreleased: &mut Release,
+    map: &MapMessageToSection,
+    options: &Generate,
+    reverted: &Reverted,
+) -> bool {
+    if reverted
+        .sha
+        .is_some_and(|sha| remove_note_from(unreleased, sha))
+    {
+        return true;
+    }
+
+    let raw_commit = match (reverted.sha.map(|sha| r.commit(sha)), reverted.title) {
+        (Some(Ok(raw_commit)), _) => raw_commit,
+        (_, Some(title)) => RawCommit {
+            title: title.into(),
+            body: "".into(),
+            sha: "".into(),
+            list_files: vec![],
+            parents: vec![],
+            author: "".into(),
+        },
+        _ => return false,
+    };
+
+    let options = Generate {
+        exclude_not_pr: Some(false),
+        ..options.clone()
+    };
+
+    let Ok((section_title, release_note)) = get_release_note(&raw_commit, None, map, &options)
+    else {
+        return false;
+    };
+
+    let Some(section) = unreleased.note_sections.get_mut(&section_title) else {
+        return false;
+    };
+
+    let with_pr = format!("{} in [", release_note.message);
+    let with_thanks = format!("{} by [", release_note.message);
+
+    match section.notes.iter().position(|note| {
+        note.scope == release_note.scope
+            && (note.message == release_note.message
+                || note.message.starts_with(&with_pr)
+                || note.message.starts_with(&with_thanks))
+    }) {
+        Some(pos) => {
+            section.notes.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Remove the note generated from the commit `sha`, using its hidden marker.
+fn remove_note_from(unreleased: &mut Release, sha: &str) -> bool {
+    for section in unreleased.note_sections.values_mut() {
+        if let Some(pos) = section.notes.iter().position(|note| note.is_from(sha)) {
+            section.notes.remove(pos);
+            return true;
+        }
+    }
+
+    false
+}
+
 fn get_release_note(
     raw_commit: &RawCommit,
     related_pr: Option<&RelatedPr>,
@@ -184,39 +320,42 @@ fn get_release_note(
         bail!("Ignoring commit. {reason}");
     }
 
-    let mut commit = match parse_commit(&raw_commit.title) {
+    let mut commit = match parse_commit(&raw_commit.title, &raw_commit.body) {
         Ok(mut commit) => {
-            let section = match map.map_section(&commit.section) {
-                Some(section) => section,
+            let target = match map.map_commit(&commit.section, commit.scope.as_deref()) {
+                Some(target) => target,
                 None => {
-                    if options.parsing == CommitMessageParsing::Strict {
+                    if options.parsing == Some(CommitMessageParsing::Strict) {
                         bail!(
                             "no corresponding commit type was found for {}",
                             commit.section
                         );
                     }
 
-                    if let Some(section) =
+                    if let Some(target) =
                         map.try_find_section((&raw_commit.title, &raw_commit.body))
                     {
-                        section
+                        target
                     } else {
-                        if options.exclude_unidentified {
+                        if options.exclude_unidentified.unwrap_or_default() {
                             bail!(
                                 "No corresponding commit type was found for {}",
                                 commit.section
                             );
                         }
-                        "Unidentified".into()
+                        MapTarget::Section(map.fallback().into())
                     }
                 }
             };
 
-            commit.section = section;
+            commit.section = match target {
+                MapTarget::Section(section) => section,
+                MapTarget::Ignore => bail!("Ignoring commit. It matches an ignore rule."),
+            };
             commit
         }
         Err(e) => {
-            if options.parsing == CommitMessageParsing::Strict {
+            if options.parsing == Some(CommitMessageParsing::Strict) {
                 bail!(
                     "Commit {}: invalid syntax: {}",
                     raw_commit.short_commit(),
@@ -224,37 +363,113 @@ fn get_release_note(
                 );
             }
 
-            let section = if let Some(section) =
-                map.try_find_section((&raw_commit.title, &raw_commit.body))
-            {
-                section
-            } else {
-                if options.exclude_unidentified {
-                    bail!("Not identified.");
+            let section = match map.try_find_section((&raw_commit.title, &raw_commit.body)) {
+                Some(MapTarget::Section(section)) => section,
+                Some(MapTarget::Ignore) => bail!("Ignoring commit. It matches an ignore rule."),
+                None => {
+                    if options.exclude_unidentified.unwrap_or_default() {
+                        bail!("Not identified.");
+                    }
+                    map.fallback().into()
                 }
-                "Unidentified".into()
             };
 
+            let (body, footers) = parse_body(&raw_commit.body);
+
             FormattedCommit {
                 section,
                 scope: None,
+                breaking: footers.iter().any(|e| e.is_breaking_change()),
                 message: raw_commit.title.clone(),
+                body,
+                footers,
             }
         }
     };
 
+    if !options.include_scope.is_empty()
+        && !commit.scope.as_ref().is_some_and(|scope| {
+            options
+                .include_scope
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(scope))
+        })
+    {
+        bail!("Ignoring commit. Its scope is not included.");
+    }
+
+    if let Some(scope) = &commit.scope {
+        if options
+            .exclude_scope
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(scope))
+        {
+            bail!("Ignoring commit. The scope {scope} is excluded.");
+        }
+    }
+
+    // the text of the BREAKING CHANGE footers explain the change
+    let mut context = Vec::new();
+
+    if commit.breaking {
+        match options.breaking.clone().unwrap_or_default() {
+            BreakingChanges::Section => {
+                commit.section = options
+                    .breaking_section
+                    .clone()
+                    .unwrap_or(DEFAULT_BREAKING_SECTION.into());
+            }
+            BreakingChanges::Mark => {
+                commit.message = format!("**BREAKING** {}", commit.message);
+            }
+        }
+
+        for footer in commit.footers.iter().filter(|e| e.is_breaking_change()) {
+            context.extend(
+                footer
+                    .value
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| line.trim().to_owned()),
+            );
+        }
+    }
+
     if let Some(related_pr) = &related_pr {
-        if !related_pr.is_pr && options.exclude_not_pr {
+        if !related_pr.is_pr && options.exclude_not_pr.unwrap_or_default() {
             bail!("No upstream pr was found");
         }
+    } else if options.exclude_not_pr.unwrap_or_default() {
+        bail!("no upstream pr was found");
+    };
+
+    if let Some(template) = &options.templates.note {
+        let related_pr = related_pr.filter(|related_pr| related_pr.is_pr);
+
+        let context = NoteContext {
+            message: commit.message,
+            scope: commit.scope.clone(),
+            sha: raw_commit.sha.clone(),
+            short_sha: raw_commit.short_commit().to_owned(),
+            pr: related_pr.map(|related_pr| related_pr.pr_id.clone()),
+            pr_url: related_pr.map(|related_pr| related_pr.url.clone()),
+            author: related_pr
+                .and_then(|related_pr| related_pr.author.clone())
+                .or_else(|| (!raw_commit.author.is_empty()).then(|| raw_commit.author.clone())),
+            author_link: related_pr.and_then(|related_pr| related_pr.author_link.clone()),
+            breaking: commit.breaking,
+            date: today(),
+        };
 
-        if !options.omit_pr_link {
+        commit.message = render(template, &context)?;
+    } else if let Some(related_pr) = &related_pr {
+        if !options.omit_pr_link.unwrap_or_default() {
             commit
                 .message
                 .push_str(&format!(" in [{}]({})", related_pr.pr_id, related_pr.url));
         }
 
-        if !options.omit_thanks {
+        if !options.omit_thanks.unwrap_or_default() {
             if let (Some(author), Some(author_link)) = (&related_pr.author, &related_pr.author_link)
             {
                 commit
@@ -262,20 +477,31 @@ fn get_release_note(
                     .push_str(&format!(" by [@{author}]({author_link})"));
             }
         }
-    } else if options.exclude_not_pr {
-        bail!("no upstream pr was found");
-    };
+    }
 
     Ok((
         commit.section,
         ReleaseSectionNote {
             scope: commit.scope,
             message: commit.message,
-            context: vec![],
+            context,
+            source: note_source(raw_commit, related_pr),
         },
     ))
 }
 
+/// Written in a hidden marker, to recognize the note after it was edited.
+fn note_source(raw_commit: &RawCommit, related_pr: Option<&RelatedPr>) -> Option<NoteSource> {
+    let source = NoteSource {
+        sha: (!raw_commit.sha.is_empty()).then(|| raw_commit.short_commit().to_owned()),
+        pr: related_pr
+            .filter(|related_pr| related_pr.is_pr)
+            .map(|related_pr| related_pr.pr_id.clone()),
+    };
+
+    (source != NoteSource::default()).then_some(source)
+}
+
 fn insert_release_note(
     unreleased: &mut Release,
     section_title: String,
@@ -299,14 +525,13 @@ fn insert_release_note(
 }
 
 #[derive(Debug, Clone)]
-enum Response {
+pub(crate) enum Response {
     Yes { reason: String },
     No,
 }
 
 impl Response {
-    #[allow(dead_code)]
-    fn bool(&self) -> bool {
+    pub fn bool(&self) -> bool {
         match self {
             Response::Yes { .. } => true,
             Response::No => false,
@@ -314,19 +539,23 @@ impl Response {
     }
 }
 
-fn commit_should_be_ignored(raw: &RawCommit) -> Response {
+/// `!log`, but not `!log_enabled`.
+static SKIP_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!(changelog|log|chglog|notes)\b").unwrap());
+
+/// In the body, which can contain code, the marker must be alone on its line.
+static SKIP_MARKER_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*!(changelog|log|chglog|notes)\s*$").unwrap());
+
+pub(crate) fn commit_should_be_ignored(raw: &RawCommit) -> Response {
     debug!("{:?}", raw);
 
     let names = ["changelog", "log", "chglog", "notes"];
 
-    let match_pat = |pat: &str| raw.title.contains(pat);
+    let match_pat = |pat: &str| raw.title.contains(pat) || raw.body.contains(pat);
 
     for n in names {
-        let patterns = [
-            format!("(skip {n})"),
-            format!("(ignore {n})"),
-            format!("!{n}"),
-        ];
+        let patterns = [format!("(skip {n})"), format!("(ignore {n})")];
 
         for pattern in &patterns {
             if match_pat(pattern) {
@@ -339,6 +568,18 @@ fn commit_should_be_ignored(raw: &RawCommit) -> Response {
         }
     }
 
+    if let Some(pattern) = SKIP_MARKER
+        .find(&raw.title)
+        .or_else(|| SKIP_MARKER_LINE.find(&raw.body))
+    {
+        return Response::Yes {
+            reason: format!(
+                "\"{}\" was matched in the commit title or description.",
+                pattern.as_str().trim()
+            ),
+        };
+    }
+
     Response::No
 }
 
@@ -353,6 +594,7 @@ mod test {
             body: "".into(),
             sha: "".into(),
             list_files: vec![],
+            parents: vec![],
             author: "".into(),
         };
 
@@ -361,5 +603,21 @@ mod test {
         raw.title = "fix: something log".into();
 
         assert!(!commit_should_be_ignored(&raw).bool());
+
+        raw.body = "if !log_enabled!(Level::Debug) {\n    return;\n}".into();
+
+        assert!(!commit_should_be_ignored(&raw).bool());
+
+        raw.title = "fix: !log_enabled check".into();
+
+        assert!(!commit_should_be_ignored(&raw).bool());
+
+        raw.body = "Only the CI is affected.\n\n!changelog\n".into();
+
+        assert!(commit_should_be_ignored(&raw).bool());
+
+        raw.body = "(skip changelog)".into();
+
+        assert!(commit_should_be_ignored(&raw).bool());
     }
 }
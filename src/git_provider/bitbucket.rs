@@ -0,0 +1,471 @@
+use std::env;
+
+use anyhow::bail;
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    header::USER_AGENT,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::utils;
+
+use super::*;
+
+const CLOUD_URL: &str = "https://bitbucket.org";
+const CLOUD_API_URL: &str = "https://api.bitbucket.org/2.0";
+
+trait ClientExt {
+    fn bitbucket_auth_env(self) -> Self;
+}
+
+impl ClientExt for RequestBuilder {
+    fn bitbucket_auth_env(self) -> Self {
+        if let Ok(token) = env::var("BITBUCKET_TOKEN") {
+            info!("bitbucket token is used");
+            self.bearer_auth(token)
+        } else if let (Ok(username), Ok(password)) = (
+            env::var("BITBUCKET_USERNAME"),
+            env::var("BITBUCKET_APP_PASSWORD"),
+        ) {
+            info!("bitbucket app password is used");
+            self.basic_auth(username, Some(password))
+        } else {
+            info!("no bitbucket token used");
+            self
+        }
+    }
+}
+
+/// Bitbucket Cloud and Bitbucket Server (Data Center) have different
+/// api and url shapes. We use the Server one when a custom url is provided.
+enum Instance {
+    Cloud,
+    Server(String),
+}
+
+impl Instance {
+    fn new(url: Option<&str>) -> Self {
+        match url.map(|url| url.trim_end_matches('/')) {
+            Some(url) if url != CLOUD_URL => Instance::Server(url.to_owned()),
+            _ => Instance::Cloud,
+        }
+    }
+}
+
+/// On Bitbucket Server, the repo is in the form 'PROJECT/repo'.
+fn server_repo_url(url: &str, repo: &str) -> anyhow::Result<String> {
+    let repo = utils::Repo::try_from(repo)?;
+
+    Ok(format!("{url}/projects/{}/repos/{}", repo.owner, repo.name))
+}
+
+fn server_api_url(url: &str, repo: &str) -> anyhow::Result<String> {
+    let repo = utils::Repo::try_from(repo)?;
+
+    Ok(format!(
+        "{url}/rest/api/1.0/projects/{}/repos/{}",
+        repo.owner, repo.name
+    ))
+}
+
+fn request_bitbucket<T: DeserializeOwned>(api: &str) -> anyhow::Result<T> {
+    let client = Client::new();
+
+    let response = client
+        .get(api)
+        .header(USER_AGENT, "my-bitbucket-client")
+        .bitbucket_auth_env()
+        .send()?;
+
+    if response.status().is_success() {
+        let obj = response.json()?;
+        Ok(obj)
+    } else {
+        bail!(format!(
+            "Bitbucket API returned status for {}: {}",
+            api,
+            response.status()
+        ))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    values: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    href: String,
+}
+
+mod cloud {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct PullRequest {
+        pub id: u64,
+        pub title: String,
+        pub description: Option<String>,
+        pub links: PullRequestLinks,
+        pub author: User,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct PullRequestLinks {
+        pub html: Link,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct User {
+        pub display_name: String,
+        pub nickname: Option<String>,
+        pub links: UserLinks,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct UserLinks {
+        pub html: Option<Link>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Commit {
+        pub links: PullRequestLinks,
+        pub author: CommitAuthor,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct CommitAuthor {
+        /// Example: 'wiiznokes <wiiznokes2@gmail.com>'
+        pub raw: String,
+        pub user: Option<User>,
+    }
+
+    impl User {
+        pub fn name(&self) -> String {
+            self.nickname.clone().unwrap_or(self.display_name.clone())
+        }
+    }
+}
+
+mod server {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    pub struct PullRequest {
+        pub id: u64,
+        pub title: String,
+        pub description: Option<String>,
+        pub author: Participant,
+        pub links: PullRequestLinks,
+        pub properties: Option<Properties>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct PullRequestLinks {
+        #[serde(rename = "self")]
+        pub self_: Vec<Link>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Participant {
+        pub user: User,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct User {
+        pub slug: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Properties {
+        #[serde(rename = "mergeCommit")]
+        pub merge_commit: Option<MergeCommit>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct MergeCommit {
+        pub id: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Commit {
+        pub author: CommitAuthor,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct CommitAuthor {
+        pub name: String,
+        pub slug: Option<String>,
+    }
+
+    impl PullRequest {
+        pub fn into_related_pr(self, url: &str) -> RelatedPr {
+            RelatedPr {
+                url: self
+                    .links
+                    .self_
+                    .into_iter()
+                    .next()
+                    .map(|e| e.href)
+                    .unwrap_or_default(),
+                pr_id: format!("#{}", self.id),
+                author_link: Some(format!("{url}/users/{}", self.author.user.slug)),
+                author: Some(self.author.user.slug),
+                title: Some(self.title),
+                body: self.description,
+                merge_commit: self.properties.and_then(|e| e.merge_commit).map(|e| e.id),
+                is_pr: true,
+            }
+        }
+    }
+}
+
+pub struct Bitbucket {
+    instance: Instance,
+}
+
+impl Bitbucket {
+    pub fn new(url: Option<&str>) -> Self {
+        Self {
+            instance: Instance::new(url),
+        }
+    }
+}
+
+impl Provider for Bitbucket {
+    fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
+        match &self.instance {
+            Instance::Cloud => {
+                let api = format!("{CLOUD_API_URL}/repositories/{repo}");
+
+                let page: Page<cloud::PullRequest> =
+                    request_bitbucket(&format!("{api}/commit/{sha}/pullrequests"))?;
+
+                match page.values.into_iter().next() {
+                    Some(pr) => Ok(RelatedPr {
+                        url: pr.links.html.href,
+                        pr_id: format!("#{}", pr.id),
+                        author: Some(pr.author.name()),
+                        author_link: pr.author.links.html.map(|e| e.href),
+                        title: Some(pr.title),
+                        body: pr.description,
+                        merge_commit: Some(sha.into()),
+                        is_pr: true,
+                    }),
+                    None => {
+                        let commit: cloud::Commit =
+                            request_bitbucket(&format!("{api}/commit/{sha}"))?;
+
+                        let (author, author_link) = match commit.author.user {
+                            Some(user) => (user.name(), user.links.html.map(|e| e.href)),
+                            None => (commit.author.raw, None),
+                        };
+
+                        Ok(RelatedPr {
+                            url: commit.links.html.href,
+                            pr_id: sha[..7].into(),
+                            author: Some(author),
+                            author_link,
+                            title: None,
+                            body: None,
+                            merge_commit: Some(sha.into()),
+                            is_pr: false,
+                        })
+                    }
+                }
+            }
+            Instance::Server(url) => {
+                let api = server_api_url(url, repo)?;
+
+                let page: Page<server::PullRequest> =
+                    request_bitbucket(&format!("{api}/commits/{sha}/pull-requests"))?;
+
+                match page.values.into_iter().next() {
+                    Some(pr) => {
+                        let mut related_pr = pr.into_related_pr(url);
+                        related_pr.merge_commit = Some(sha.into());
+                        Ok(related_pr)
+                    }
+                    None => {
+                        let commit: server::Commit =
+                            request_bitbucket(&format!("{api}/commits/{sha}"))?;
+
+                        Ok(RelatedPr {
+                            url: format!("{}/commits/{sha}", server_repo_url(url, repo)?),
+                            pr_id: sha[..7].into(),
+                            author_link: commit
+                                .author
+                                .slug
+                                .map(|slug| format!("{url}/users/{slug}")),
+                            author: Some(commit.author.name),
+                            title: None,
+                            body: None,
+                            merge_commit: Some(sha.into()),
+                            is_pr: false,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+        let link = match &self.instance {
+            Instance::Cloud => match diff_tags.prev_tag() {
+                Some(prev) => {
+                    format!(
+                        "{CLOUD_URL}/{repo}/branches/compare/{}%0D{prev}",
+                        diff_tags.new_tag()
+                    )
+                }
+                None => {
+                    format!("{CLOUD_URL}/{repo}/commits/tag/{}", diff_tags.new_tag())
+                }
+            },
+            Instance::Server(url) => {
+                let base = server_repo_url(url, repo)?;
+
+                match diff_tags.prev_tag() {
+                    Some(prev) => {
+                        format!(
+                            "{base}/compare/commits?sourceBranch=refs/tags/{}&targetBranch=refs/tags/{prev}",
+                            diff_tags.new_tag()
+                        )
+                    }
+                    None => {
+                        format!("{base}/commits?until=refs/tags/{}", diff_tags.new_tag())
+                    }
+                }
+            }
+        };
+
+        Ok(link)
+    }
+
+    /// Bitbucket doesn't have releases, so we link to the tag.
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
+        let link = match &self.instance {
+            Instance::Cloud => format!("{CLOUD_URL}/{repo}/src/{tag}"),
+            Instance::Server(url) => {
+                format!("{}/browse?at=refs/tags/{tag}", server_repo_url(url, repo)?)
+            }
+        };
+
+        Ok(link)
+    }
+
+    fn milestone_prs(&self, _repo: &str, _milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        bail!("Bitbucket pull requests don't have milestones")
+    }
+
+    fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        match &self.instance {
+            // The merge commit hash returned by Bitbucket Cloud is abbreviated,
+            // so it can't be matched against the commits of the repo.
+            Instance::Cloud => bail!("Bitbucket Cloud doesn't expose the full merge commit hash"),
+            Instance::Server(url) => {
+                let page: Page<server::PullRequest> = request_bitbucket(&format!(
+                    "{}/pull-requests?state=MERGED&order=NEWEST&limit={n}",
+                    server_api_url(url, repo)?
+                ))?;
+
+                let res = page
+                    .values
+                    .into_iter()
+                    .map(|e| e.into_related_pr(url))
+                    .filter(|e| e.merge_commit.is_some())
+                    .collect();
+
+                Ok(res)
+            }
+        }
+    }
+
+    fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+        let commit_url = match &self.instance {
+            Instance::Cloud => format!("{CLOUD_URL}/{repo}/commits/{}", raw_commit.sha),
+            Instance::Server(url) => match server_repo_url(url, repo) {
+                Ok(base) => format!("{base}/commits/{}", raw_commit.sha),
+                Err(e) => {
+                    warn!("{e}");
+                    return None;
+                }
+            },
+        };
+
+        Some(RelatedPr {
+            url: commit_url,
+            pr_id: raw_commit.sha[..7].into(),
+            author: Some(raw_commit.author.clone()),
+            // the commit author name can't be mapped to a Bitbucket account offline
+            author_link: None,
+            title: Some(raw_commit.title.clone()),
+            body: Some(raw_commit.body.clone()),
+            merge_commit: Some(raw_commit.sha.clone()),
+            is_pr: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[ignore = "network"]
+    #[test]
+    fn pr() {
+        let res = Bitbucket::new(None)
+            .related_pr("atlassian/python-bitbucket", "8a2a3fa")
+            .unwrap();
+
+        dbg!(&res);
+    }
+
+    #[test]
+    fn link() {
+        let diff_tags = DiffTags {
+            prev: Some(Version::new(0, 1, 0)),
+            new: Version::new(0, 1, 1),
+            prefix: String::new(),
+        };
+
+        let res = Bitbucket::new(None)
+            .diff_link("workspace/repo", &diff_tags)
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.org/workspace/repo/branches/compare/0.1.1%0D0.1.0".to_owned()
+        );
+
+        let res = Bitbucket::new(Some("https://bitbucket.example.com"))
+            .diff_link("PROJ/repo", &diff_tags)
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.example.com/projects/PROJ/repos/repo/compare/commits?sourceBranch=refs/tags/0.1.1&targetBranch=refs/tags/0.1.0".to_owned()
+        );
+
+        let res = Bitbucket::new(None)
+            .release_link("workspace/repo", "0.1.1")
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.org/workspace/repo/src/0.1.1".to_owned()
+        );
+
+        let res = Bitbucket::new(Some("https://bitbucket.example.com/"))
+            .release_link("PROJ/repo", "0.1.1")
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://bitbucket.example.com/projects/PROJ/repos/repo/browse?at=refs/tags/0.1.1"
+                .to_owned()
+        );
+    }
+}
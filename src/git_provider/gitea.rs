@@ -0,0 +1,388 @@
+use std::env;
+
+use anyhow::{anyhow, bail};
+use reqwest::{
+    blocking::{Client, RequestBuilder},
+    header::{AUTHORIZATION, USER_AGENT},
+    StatusCode, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::*;
+
+trait ClientExt {
+    fn gitea_auth_env(self) -> Self;
+}
+
+impl ClientExt for RequestBuilder {
+    fn gitea_auth_env(self) -> Self {
+        if let Ok(token) = env::var("GITEA_TOKEN").or_else(|_| env::var("FORGEJO_TOKEN")) {
+            info!("gitea token is used");
+            self.header(AUTHORIZATION, format!("token {token}"))
+        } else {
+            info!("no gitea token used");
+            self
+        }
+    }
+}
+
+/// There is no canonical instance, so the url is required.
+/// Fallback to the one defined in Gitea/Forgejo Actions. `GITHUB_SERVER_URL` is only
+/// trusted there, because Github Actions defines it too.
+fn base_url(url: Option<&str>) -> anyhow::Result<String> {
+    let in_actions =
+        env::var_os("GITEA_ACTIONS").is_some() || env::var_os("FORGEJO_ACTIONS").is_some();
+
+    let url = match url {
+        Some(url) => url.to_owned(),
+        None => env::var("GITHUB_SERVER_URL")
+            .ok()
+            .filter(|_| in_actions)
+            .ok_or_else(|| {
+                anyhow!("No instance url was provided for Gitea. Example: 'https://codeberg.org'.")
+            })?,
+    };
+
+    Ok(url.trim_end_matches('/').to_owned())
+}
+
+/// `None` when the API returns 404.
+fn request_gitea_opt<T: DeserializeOwned>(api: &str) -> anyhow::Result<Option<T>> {
+    let client = Client::new();
+
+    let response = client
+        .get(api)
+        .header(USER_AGENT, "my-gitea-client")
+        .gitea_auth_env()
+        .send()?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        Ok(None)
+    } else if response.status().is_success() {
+        let obj = response.json()?;
+        Ok(Some(obj))
+    } else {
+        bail!(format!(
+            "Gitea API returned status for {}: {}",
+            api,
+            response.status()
+        ))
+    }
+}
+
+fn request_gitea<T: DeserializeOwned>(api: &str) -> anyhow::Result<T> {
+    request_gitea_opt(api)?.ok_or_else(|| {
+        anyhow!(
+            "Gitea API returned status for {}: {}",
+            api,
+            StatusCode::NOT_FOUND
+        )
+    })
+}
+
+/// Default maximum of the Gitea API.
+const PAGE_LIMIT: usize = 50;
+
+/// Follow the `page` parameter until an empty page, or until `max` items are kept by `f`.
+fn request_gitea_pages<T, U>(
+    api: &str,
+    max: usize,
+    mut f: impl FnMut(T) -> Option<U>,
+) -> anyhow::Result<Vec<U>>
+where
+    T: DeserializeOwned,
+{
+    let mut res = Vec::new();
+
+    for page in 1.. {
+        let items: Vec<T> = request_gitea(&format!("{api}&limit={PAGE_LIMIT}&page={page}"))?;
+
+        if items.is_empty() {
+            break;
+        }
+
+        res.extend(items.into_iter().filter_map(&mut f));
+
+        if res.len() >= max {
+            res.truncate(max);
+            break;
+        }
+    }
+
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    user: User,
+    merged: bool,
+    merge_commit_sha: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    html_url: String,
+    user: User,
+    pull_request: Option<PullRequestMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestMeta {
+    merged: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    html_url: String,
+    author: Option<User>,
+    commit: CommitDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitDetail {
+    author: CommitAuthor,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitAuthor {
+    name: String,
+}
+
+impl PullRequest {
+    fn into_related_pr(self, base: &str) -> RelatedPr {
+        RelatedPr {
+            url: self.html_url,
+            pr_id: format!("#{}", self.number),
+            author_link: Some(format!("{base}/{}", self.user.login)),
+            author: Some(self.user.login),
+            title: Some(self.title),
+            body: self.body,
+            merge_commit: self.merge_commit_sha,
+            is_pr: true,
+        }
+    }
+}
+
+pub struct Gitea {
+    url: Option<String>,
+}
+
+impl Gitea {
+    pub fn new(url: Option<&str>) -> Self {
+        Self {
+            url: url.map(ToOwned::to_owned),
+        }
+    }
+}
+
+impl Provider for Gitea {
+    fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
+        let base = base_url(self.url.as_deref())?;
+        let api = format!("{base}/api/v1/repos/{repo}");
+
+        // return 404 when the commit is not attached to a pull request
+        match request_gitea_opt::<PullRequest>(&format!("{api}/commits/{sha}/pull"))? {
+            Some(pull_request) => {
+                let mut related_pr = pull_request.into_related_pr(&base);
+                related_pr.merge_commit = Some(sha.into());
+                Ok(related_pr)
+            }
+            None => {
+                debug!("no pull request found for {sha}");
+
+                let commit: Commit = request_gitea(&format!("{api}/git/commits/{sha}"))?;
+
+                let (author, author_link) = match commit.author {
+                    Some(user) => (user.login.clone(), Some(format!("{base}/{}", user.login))),
+                    None => (commit.commit.author.name, None),
+                };
+
+                Ok(RelatedPr {
+                    url: commit.html_url,
+                    pr_id: sha[..7].into(),
+                    author: Some(author),
+                    author_link,
+                    title: None,
+                    body: None,
+                    merge_commit: Some(sha.into()),
+                    is_pr: false,
+                })
+            }
+        }
+    }
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+        let base = format!("{}/{repo}", base_url(self.url.as_deref())?);
+
+        let link = match diff_tags.prev_tag() {
+            Some(prev) => {
+                format!("{base}/compare/{prev}...{}", diff_tags.new_tag())
+            }
+            None => {
+                format!("{base}/commits/tag/{}", diff_tags.new_tag())
+            }
+        };
+
+        Ok(link)
+    }
+
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
+        Ok(format!(
+            "{}/{repo}/releases/tag/{tag}",
+            base_url(self.url.as_deref())?
+        ))
+    }
+
+    fn milestone_prs(&self, repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        let base = base_url(self.url.as_deref())?;
+
+        // the milestones parameter accept names
+        let res = request_gitea_pages(
+            Url::parse_with_params(
+                &format!("{base}/api/v1/repos/{repo}/issues"),
+                [
+                    ("type", "pulls"),
+                    ("state", "closed"),
+                    ("milestones", milestone),
+                ],
+            )?
+            .as_str(),
+            usize::MAX,
+            |e: Issue| {
+                e.pull_request
+                    .as_ref()
+                    .is_some_and(|pr| pr.merged)
+                    .then(|| RelatedPr {
+                        url: e.html_url,
+                        pr_id: format!("#{}", e.number),
+                        author_link: Some(format!("{base}/{}", e.user.login)),
+                        author: Some(e.user.login),
+                        title: Some(e.title),
+                        body: e.body,
+                        merge_commit: None,
+                        is_pr: true,
+                    })
+            },
+        )?;
+
+        Ok(res)
+    }
+
+    fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        let base = base_url(self.url.as_deref())?;
+
+        let res = request_gitea_pages(
+            &format!("{base}/api/v1/repos/{repo}/pulls?state=closed&sort=recentupdate"),
+            n,
+            |e: PullRequest| {
+                (e.merged && e.merge_commit_sha.is_some()).then(|| e.into_related_pr(&base))
+            },
+        )?;
+
+        Ok(res)
+    }
+
+    fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+        let base = match base_url(self.url.as_deref()) {
+            Ok(base) => base,
+            Err(e) => {
+                warn!("{e}");
+                return None;
+            }
+        };
+
+        Some(RelatedPr {
+            url: format!("{base}/{repo}/commit/{}", raw_commit.sha),
+            pr_id: raw_commit.sha[..7].into(),
+            author: Some(raw_commit.author.clone()),
+            author_link: Some(format!("{base}/{}", raw_commit.author)),
+            title: Some(raw_commit.title.clone()),
+            body: Some(raw_commit.body.clone()),
+            merge_commit: Some(raw_commit.sha.clone()),
+            is_pr: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[ignore = "network"]
+    #[test]
+    fn pr() {
+        let res = Gitea::new(Some("https://codeberg.org"))
+            .related_pr("forgejo/forgejo", "2c1f43d")
+            .unwrap();
+
+        dbg!(&res);
+    }
+
+    #[test]
+    fn link() {
+        let res = Gitea::new(Some("https://codeberg.org/"))
+            .diff_link(
+                "forgejo/forgejo",
+                &DiffTags {
+                    prev: None,
+                    new: Version::new(0, 1, 0),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://codeberg.org/forgejo/forgejo/commits/tag/0.1.0".to_owned()
+        );
+
+        let res = Gitea::new(Some("https://codeberg.org"))
+            .diff_link(
+                "forgejo/forgejo",
+                &DiffTags {
+                    prev: Some(Version::new(0, 1, 0)),
+                    new: Version::new(0, 1, 1),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://codeberg.org/forgejo/forgejo/compare/0.1.0...0.1.1".to_owned()
+        );
+    }
+
+    #[ignore = "network"]
+    #[test]
+    fn milestone() {
+        let res = Gitea::new(Some("https://codeberg.org"))
+            .milestone_prs("forgejo/forgejo", "v9.0.0")
+            .unwrap();
+
+        dbg!(&res);
+    }
+
+    #[ignore = "network"]
+    #[test]
+    fn lasts() {
+        let res = Gitea::new(Some("https://codeberg.org"))
+            .last_prs("forgejo/forgejo", 3)
+            .unwrap();
+
+        dbg!(&res);
+    }
+}
@@ -49,7 +49,7 @@ fn request_github(api: &str) -> anyhow::Result<Value> {
     }
 }
 
-fn request_github_graphql(query: &str) -> anyhow::Result<Value> {
+fn request_github_graphql(api: &str, query: &str) -> anyhow::Result<Value> {
     let client = Client::new();
 
     let request_body = json!({
@@ -57,7 +57,7 @@ fn request_github_graphql(query: &str) -> anyhow::Result<Value> {
     });
 
     let response = client
-        .post("https://api.github.com/graphql")
+        .post(api)
         .header(USER_AGENT, "my-github-client")
         .bearer_auth_env("GITHUB_TOKEN")
         .json(&request_body)
@@ -74,13 +74,156 @@ fn request_github_graphql(query: &str) -> anyhow::Result<Value> {
     }
 }
 
-pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
-    let json = request_github(&format!(
-        "https://api.github.com/repos/{repo}/commits/{sha}/pulls"
-    ))?;
+const SERVER_URL: &str = "https://github.com";
+const API_URL: &str = "https://api.github.com";
+const GRAPHQL_URL: &str = "https://api.github.com/graphql";
 
-    match json.get(0) {
-        Some(obj) => {
+pub struct Github {
+    server_url: String,
+    api_url: String,
+    graphql_url: String,
+}
+
+impl Github {
+    /// With a custom url, we assume it is a Github Enterprise Server instance.
+    /// Otherwise, fallback to the urls defined in Github Actions, then to github.com.
+    pub fn new(url: Option<&str>) -> Self {
+        match url.map(|url| url.trim_end_matches('/')) {
+            Some(url) if url != SERVER_URL => Self {
+                server_url: url.to_owned(),
+                api_url: format!("{url}/api/v3"),
+                graphql_url: format!("{url}/api/graphql"),
+            },
+            _ => Self {
+                server_url: env::var("GITHUB_SERVER_URL").unwrap_or(SERVER_URL.to_owned()),
+                api_url: env::var("GITHUB_API_URL").unwrap_or(API_URL.to_owned()),
+                graphql_url: env::var("GITHUB_GRAPHQL_URL").unwrap_or(GRAPHQL_URL.to_owned()),
+            },
+        }
+    }
+}
+
+impl Provider for Github {
+    fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
+        let json = request_github(&format!(
+            "{}/repos/{repo}/commits/{sha}/pulls",
+            self.api_url
+        ))?;
+
+        match json.get(0) {
+            Some(obj) => {
+                let url = obj
+                    .get("html_url")
+                    .ok_or(anyhow!("no html_url found"))?
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+
+                let pr_id = obj
+                    .get("number")
+                    .ok_or(anyhow!("no number found"))?
+                    .as_u64()
+                    .unwrap();
+
+                let pr_id = format!("#{}", pr_id);
+
+                let author = obj
+                    .get("user")
+                    .ok_or(anyhow!("no user found"))?
+                    .get("login")
+                    .ok_or(anyhow!("no login found"))?
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+
+                let author_link = format!("{}/{}", self.server_url, author);
+
+                let title = obj
+                    .get("title")
+                    .ok_or(anyhow!("no title found"))?
+                    .to_string();
+                let body = obj.get("body").ok_or(anyhow!("no body found"))?.to_string();
+
+                Ok(RelatedPr {
+                    url,
+                    author: Some(author),
+                    pr_id,
+                    author_link: Some(author_link),
+                    title: Some(title),
+                    body: Some(body),
+                    merge_commit: Some(sha.into()),
+                    is_pr: true,
+                })
+            }
+            None => {
+                let obj = request_github(&format!("{}/repos/{repo}/commits/{sha}", self.api_url))?;
+
+                let url = obj
+                    .get("html_url")
+                    .ok_or(anyhow!("no html_url found"))?
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+
+                let author = obj
+                    .get("author")
+                    .ok_or(anyhow!("no user found"))?
+                    .get("login")
+                    .ok_or(anyhow!("no login found"))?
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+
+                let author_link = format!("{}/{}", self.server_url, author);
+
+                Ok(RelatedPr {
+                    url,
+                    author: Some(author),
+                    pr_id: sha[..7].into(),
+                    author_link: Some(author_link),
+                    title: None,
+                    body: None,
+                    merge_commit: Some(sha.into()),
+                    is_pr: false,
+                })
+            }
+        }
+    }
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+        let base = format!("{}/{repo}", self.server_url);
+
+        let link = match diff_tags.prev_tag() {
+            Some(prev) => {
+                format!("{base}/compare/{prev}...{}", diff_tags.new_tag())
+            }
+            None => {
+                format!("{base}/commits/{}", diff_tags.new_tag())
+            }
+        };
+
+        Ok(link)
+    }
+
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
+        Ok(format!("{}/{repo}/releases/tag/{tag}", self.server_url))
+    }
+
+    fn milestone_prs(&self, repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        let json = request_github(&format!(
+            "{}/search/issues?q=repo:{repo}+is:pr+is:merged+milestone:{milestone}",
+            self.api_url
+        ))?;
+
+        let array = json
+            .get("items")
+            .expect("no items")
+            .as_array()
+            .expect("not an array");
+
+        let mut res = Vec::new();
+
+        for obj in array {
             let url = obj
                 .get("html_url")
                 .ok_or(anyhow!("no html_url found"))?
@@ -105,311 +248,298 @@ pub fn request_related_pr(repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
                 .unwrap()
                 .to_string();
 
-            let author_link = format!("https://github.com/{}", author);
+            let author_link = format!("{}/{}", self.server_url, author);
 
             let title = obj
                 .get("title")
                 .ok_or(anyhow!("no title found"))?
                 .to_string();
-            let body = obj.get("body").ok_or(anyhow!("no body found"))?.to_string();
+            let body = obj
+                .get("body")
+                .ok_or(anyhow!("no title found"))?
+                .to_string();
 
-            Ok(RelatedPr {
+            res.push(RelatedPr {
                 url,
-                author: Some(author),
                 pr_id,
+                author: Some(author),
                 author_link: Some(author_link),
                 title: Some(title),
                 body: Some(body),
-                merge_commit: Some(sha.into()),
+                merge_commit: None,
                 is_pr: true,
-            })
+            });
         }
-        None => {
-            let obj = request_github(&format!(
-                "https://api.github.com/repos/{repo}/commits/{sha}"
-            ))?;
-
-            let url = obj
-                .get("html_url")
-                .ok_or(anyhow!("no html_url found"))?
-                .as_str()
-                .unwrap()
-                .to_string();
 
-            let author = obj
-                .get("author")
-                .ok_or(anyhow!("no user found"))?
-                .get("login")
-                .ok_or(anyhow!("no login found"))?
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            let author_link = format!("https://github.com/{}", author);
-
-            Ok(RelatedPr {
-                url,
-                author: Some(author),
-                pr_id: sha[..7].into(),
-                author_link: Some(author_link),
-                title: None,
-                body: None,
-                merge_commit: Some(sha.into()),
-                is_pr: false,
-            })
-        }
+        Ok(res)
     }
-}
-
-pub fn diff_link(repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
-    let base = format!("https://github.com/{repo}");
 
-    let link = match &diff_tags.prev {
-        Some(prev) => {
-            format!("{base}/compare/{prev}...{}", diff_tags.new)
+    fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        let query = r##"
+    {
+      repository(name: "#name", owner: "#owner") {
+        pullRequests(
+          first: #first
+          states: MERGED
+          orderBy: { field: UPDATED_AT, direction: DESC }
+        ) {
+          nodes {
+            number
+            title
+            body
+            url
+            author {
+              login
+            }
+            mergeCommit {
+              oid
+            }
+          }
         }
-        None => {
-            format!("{base}/commits/{}", diff_tags.new)
-        }
-    };
-
-    Ok(link)
-}
-
-pub fn release_link(repo: &str, tag: &str) -> anyhow::Result<String> {
-    Ok(format!("https://github.com/{repo}/releases/tag/{tag}"))
-}
-
-pub fn milestone_prs(repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
-    let json = request_github(&format!(
-        "https://api.github.com/search/issues?q=repo:{repo}+is:pr+is:merged+milestone:{milestone}"
-    ))?;
-
-    let array = json
-        .get("items")
-        .expect("no items")
-        .as_array()
-        .expect("not an array");
-
-    let mut res = Vec::new();
-
-    for obj in array {
-        let url = obj
-            .get("html_url")
-            .ok_or(anyhow!("no html_url found"))?
-            .as_str()
-            .unwrap()
-            .to_string();
-
-        let pr_id = obj
-            .get("number")
-            .ok_or(anyhow!("no number found"))?
-            .as_u64()
-            .unwrap();
-
-        let pr_id = format!("#{}", pr_id);
-
-        let author = obj
-            .get("user")
-            .ok_or(anyhow!("no user found"))?
-            .get("login")
-            .ok_or(anyhow!("no login found"))?
-            .as_str()
-            .unwrap()
-            .to_string();
-
-        let author_link = format!("https://github.com/{}", author);
-
-        let title = obj
-            .get("title")
-            .ok_or(anyhow!("no title found"))?
-            .to_string();
-        let body = obj
-            .get("body")
-            .ok_or(anyhow!("no title found"))?
-            .to_string();
-
-        res.push(RelatedPr {
-            url,
-            pr_id,
-            author: Some(author),
-            author_link: Some(author_link),
-            title: Some(title),
-            body: Some(body),
-            merge_commit: None,
-            is_pr: true,
-        });
+      }
     }
+    "##;
 
-    Ok(res)
-}
+        let mut interpolate = TextInterpolate::new(query.into(), "#", "");
 
-pub fn last_prs(repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
-    let query = r##"
-{
-  repository(name: "#name", owner: "#owner") {
-    pullRequests(
-      first: #first
-      states: MERGED
-      orderBy: { field: UPDATED_AT, direction: DESC }
-    ) {
-      nodes {
-        number
-        title
-        body
-        url
-        author {
-          login
-        }
-        mergeCommit {
-          oid
-        }
-      }
-    }
-  }
-}
-"##;
+        let repo = utils::Repo::try_from(repo)?;
 
-    let mut interpolate = TextInterpolate::new(query.into(), "#", "");
+        interpolate.interpolate("name", &repo.name);
+        interpolate.interpolate("owner", &repo.owner);
+        interpolate.interpolate("first", &n.to_string());
 
-    let repo = utils::Repo::try_from(repo)?;
+        let value = request_github_graphql(&self.graphql_url, &interpolate.text())?;
 
-    interpolate.interpolate("name", &repo.name);
-    interpolate.interpolate("owner", &repo.owner);
-    interpolate.interpolate("first", &n.to_string());
+        #[derive(Debug, Deserialize)]
+        struct Response {
+            data: Data,
+        }
 
-    let value = request_github_graphql(&interpolate.text())?;
+        #[derive(Debug, Deserialize)]
+        struct Data {
+            repository: Repository,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct Response {
-        data: Data,
-    }
+        #[derive(Debug, Deserialize)]
+        struct Repository {
+            #[serde(rename = "pullRequests")]
+            pull_requests: PullRequests,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct Data {
-        repository: Repository,
-    }
+        #[derive(Debug, Deserialize)]
+        struct PullRequests {
+            nodes: Vec<PullRequest>,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct Repository {
-        #[serde(rename = "pullRequests")]
-        pull_requests: PullRequests,
-    }
+        #[derive(Debug, Deserialize)]
+        struct PullRequest {
+            author: Author,
+            body: String,
+            #[serde(rename = "mergeCommit")]
+            merge_commit: MergeCommit,
+            number: u32,
+            title: String,
+            url: String,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct PullRequests {
-        nodes: Vec<PullRequest>,
-    }
+        #[derive(Debug, Deserialize)]
+        struct Author {
+            login: String,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct PullRequest {
-        author: Author,
-        body: String,
-        #[serde(rename = "mergeCommit")]
-        merge_commit: MergeCommit,
-        number: u32,
-        title: String,
-        url: String,
-    }
+        #[derive(Debug, Deserialize)]
+        struct MergeCommit {
+            oid: String,
+        }
 
-    #[derive(Debug, Deserialize)]
-    struct Author {
-        login: String,
-    }
+        let response = serde_json::value::from_value::<Response>(value)?;
+
+        let res = response
+            .data
+            .repository
+            .pull_requests
+            .nodes
+            .into_iter()
+            .map(|e| RelatedPr {
+                url: e.url,
+                pr_id: format!("#{}", e.number),
+                author_link: Some(format!("{}/{}", self.server_url, e.author.login)),
+                author: Some(e.author.login),
+                title: Some(e.title),
+                body: Some(e.body),
+                merge_commit: Some(e.merge_commit.oid),
+                is_pr: true,
+            })
+            .collect();
 
-    #[derive(Debug, Deserialize)]
-    struct MergeCommit {
-        oid: String,
+        Ok(res)
     }
 
-    let response = serde_json::value::from_value::<Response>(value)?;
-
-    let res = response
-        .data
-        .repository
-        .pull_requests
-        .nodes
-        .into_iter()
-        .map(|e| RelatedPr {
-            url: e.url,
-            pr_id: format!("#{}", e.number),
-            author_link: Some(format!("https://github.com/{}", e.author.login)),
-            author: Some(e.author.login),
-            title: Some(e.title),
-            body: Some(e.body),
-            merge_commit: Some(e.merge_commit.oid),
-            is_pr: true,
+    fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+        Some(RelatedPr {
+            url: format!("{}/{repo}/commit/{}", self.server_url, raw_commit.sha),
+            pr_id: raw_commit.sha[..7].into(),
+            author: Some(raw_commit.author.clone()),
+            author_link: Some(format!("{}/{}", self.server_url, raw_commit.author)),
+            title: Some(raw_commit.title.clone()),
+            body: Some(raw_commit.body.clone()),
+            merge_commit: Some(raw_commit.sha.clone()),
+            is_pr: false,
         })
-        .collect();
-
-    Ok(res)
-}
-
-pub fn offline_related_pr(repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
-    Some(RelatedPr {
-        url: format!("https://github.com/{repo}/commit/{}", raw_commit.sha),
-        pr_id: raw_commit.sha[..7].into(),
-        author: Some(raw_commit.author.clone()),
-        author_link: Some(format!("https://github.com/{}", raw_commit.author)),
-        title: Some(raw_commit.title.clone()),
-        body: Some(raw_commit.body.clone()),
-        merge_commit: Some(raw_commit.sha.clone()),
-        is_pr: false,
-    })
+    }
 }
 
 #[cfg(test)]
 mod test {
 
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
     use super::*;
 
     #[ignore = "403"]
     #[test]
     fn pr() {
-        let res = request_related_pr("wiiznokes/fan-control", "74c8a3c").unwrap();
+        let res = Github::new(None)
+            .related_pr("wiiznokes/fan-control", "74c8a3c")
+            .unwrap();
 
         dbg!(&res);
 
-        let res = request_related_pr("wiiznokes/changen", "84d7fa4").unwrap();
+        let res = Github::new(None)
+            .related_pr("wiiznokes/changen", "84d7fa4")
+            .unwrap();
 
         dbg!(&res);
     }
 
     #[test]
     fn link() {
-        let res = diff_link(
-            "wiiznokes/fan-control",
-            &DiffTags {
-                prev: None,
-                new: Version::new(0, 1, 0),
-            },
-        )
-        .unwrap();
+        let res = Github::new(None)
+            .diff_link(
+                "wiiznokes/fan-control",
+                &DiffTags {
+                    prev: None,
+                    new: Version::new(0, 1, 0),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
 
         assert_eq!(
             res,
             "https://github.com/wiiznokes/fan-control/commits/0.1.0".to_owned()
         );
 
-        let res = diff_link(
-            "wiiznokes/fan-control",
-            &DiffTags {
-                prev: Some(Version::new(0, 1, 0)),
-                new: Version::new(0, 1, 1),
-            },
-        )
-        .unwrap();
+        let res = Github::new(None)
+            .diff_link(
+                "wiiznokes/fan-control",
+                &DiffTags {
+                    prev: Some(Version::new(0, 1, 0)),
+                    new: Version::new(0, 1, 1),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
 
         assert_eq!(
             res,
             "https://github.com/wiiznokes/fan-control/compare/0.1.0...0.1.1".to_owned()
         );
+
+        let res = Github::new(None)
+            .diff_link(
+                "wiiznokes/fan-control",
+                &DiffTags {
+                    prev: Some(Version::new(0, 1, 0)),
+                    new: Version::new(0, 1, 1),
+                    prefix: "v".into(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://github.com/wiiznokes/fan-control/compare/v0.1.0...v0.1.1".to_owned()
+        );
+    }
+
+    #[test]
+    fn enterprise_link() {
+        let github = Github::new(Some("https://github.example.com/"));
+
+        assert_eq!(
+            github.api_url,
+            "https://github.example.com/api/v3".to_owned()
+        );
+        assert_eq!(
+            github.graphql_url,
+            "https://github.example.com/api/graphql".to_owned()
+        );
+
+        let res = github
+            .release_link("wiiznokes/fan-control", "0.1.0")
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://github.example.com/wiiznokes/fan-control/releases/tag/0.1.0".to_owned()
+        );
+    }
+
+    #[test]
+    fn mock_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+
+            let body = r#"[{
+                "html_url": "https://github.example.com/wiiznokes/changen/pull/42",
+                "number": 42,
+                "user": { "login": "wiiznokes" },
+                "title": "fix: something",
+                "body": ""
+            }]"#;
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+
+            request_line
+        });
+
+        let res = Github::new(Some(&url))
+            .related_pr("wiiznokes/changen", "84d7fa4")
+            .unwrap();
+
+        let request_line = server.join().unwrap();
+
+        assert!(
+            request_line.starts_with("GET /api/v3/repos/wiiznokes/changen/commits/84d7fa4/pulls ")
+        );
+        assert_eq!(res.pr_id, "#42".to_owned());
+        assert_eq!(res.author_link, Some(format!("{url}/wiiznokes")));
     }
 
     #[ignore = "403"]
     #[test]
     fn milestone() {
-        let res = milestone_prs("iced-rs/iced", "0.13").unwrap();
+        let res = Github::new(None)
+            .milestone_prs("iced-rs/iced", "0.13")
+            .unwrap();
 
         dbg!(&res);
     }
@@ -417,7 +547,7 @@ mod test {
     #[ignore = "403"]
     #[test]
     fn lasts() {
-        let res = last_prs("iced-rs/iced", 3).unwrap();
+        let res = Github::new(None).last_prs("iced-rs/iced", 3).unwrap();
 
         dbg!(&res);
     }
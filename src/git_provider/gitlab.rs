@@ -0,0 +1,349 @@
+use std::env;
+
+use anyhow::bail;
+use reqwest::{
+    blocking::{Client, RequestBuilder, Response},
+    header::USER_AGENT,
+    Url,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::*;
+
+const DEFAULT_URL: &str = "https://gitlab.com";
+
+trait ClientExt {
+    fn gitlab_auth_env(self) -> Self;
+}
+
+impl ClientExt for RequestBuilder {
+    fn gitlab_auth_env(self) -> Self {
+        if let Ok(token) = env::var("GITLAB_TOKEN") {
+            info!("gitlab token is used");
+            self.header("PRIVATE-TOKEN", token)
+        } else if let Ok(token) = env::var("CI_JOB_TOKEN") {
+            info!("gitlab job token is used");
+            self.header("JOB-TOKEN", token)
+        } else {
+            info!("no gitlab token used");
+            self
+        }
+    }
+}
+
+/// Base url of the instance. Fallback to the one defined in Gitlab CI, then to gitlab.com.
+fn base_url(url: Option<&str>) -> String {
+    let url = match url {
+        Some(url) => url.to_owned(),
+        None => env::var("CI_SERVER_URL").unwrap_or(DEFAULT_URL.to_owned()),
+    };
+
+    url.trim_end_matches('/').to_owned()
+}
+
+/// The api accept the url-encoded path of the project as an id.
+/// Example: 'group/subgroup/project' => 'group%2Fsubgroup%2Fproject'
+fn project_id(repo: &str) -> String {
+    repo.replace('/', "%2F")
+}
+
+fn send_gitlab(api: &str) -> anyhow::Result<Response> {
+    let client = Client::new();
+
+    let response = client
+        .get(api)
+        .header(USER_AGENT, "my-gitlab-client")
+        .gitlab_auth_env()
+        .send()?;
+
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        bail!(format!(
+            "Gitlab API returned status for {}: {}",
+            api,
+            response.status()
+        ))
+    }
+}
+
+fn request_gitlab<T: DeserializeOwned>(api: &str) -> anyhow::Result<T> {
+    Ok(send_gitlab(api)?.json()?)
+}
+
+/// Follow the `x-next-page` header until the last page, or until `max` items are kept by `f`.
+fn request_gitlab_pages<T, U>(
+    api: &Url,
+    max: usize,
+    mut f: impl FnMut(T) -> Option<U>,
+) -> anyhow::Result<Vec<U>>
+where
+    T: DeserializeOwned,
+{
+    let mut res = Vec::new();
+    let mut page = String::from("1");
+
+    loop {
+        let mut url = api.clone();
+        url.query_pairs_mut().append_pair("page", &page);
+
+        let response = send_gitlab(url.as_str())?;
+
+        let next_page = response
+            .headers()
+            .get("x-next-page")
+            .and_then(|e| e.to_str().ok())
+            .unwrap_or_default()
+            .trim()
+            .to_owned();
+
+        let items: Vec<T> = response.json()?;
+        res.extend(items.into_iter().filter_map(&mut f));
+
+        if res.len() >= max {
+            res.truncate(max);
+            break;
+        }
+
+        if next_page.is_empty() {
+            break;
+        }
+
+        page = next_page;
+    }
+
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    web_url: String,
+    author: Author,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    username: String,
+    web_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    web_url: String,
+    author_name: String,
+}
+
+impl MergeRequest {
+    fn into_related_pr(self, merge_commit: Option<String>) -> RelatedPr {
+        RelatedPr {
+            url: self.web_url,
+            pr_id: format!("!{}", self.iid),
+            author: Some(self.author.username),
+            author_link: Some(self.author.web_url),
+            title: Some(self.title),
+            body: self.description,
+            merge_commit: merge_commit
+                .or(self.merge_commit_sha)
+                .or(self.squash_commit_sha),
+            is_pr: true,
+        }
+    }
+}
+
+pub struct Gitlab {
+    url: String,
+}
+
+impl Gitlab {
+    pub fn new(url: Option<&str>) -> Self {
+        Self { url: base_url(url) }
+    }
+
+    /// Merged merge requests of `repo`, with url-encoded `params`.
+    fn merged_requests_api(&self, repo: &str, params: &[(&str, &str)]) -> anyhow::Result<Url> {
+        let mut api = Url::parse(&format!(
+            "{}/api/v4/projects/{}/merge_requests?state=merged&per_page=100",
+            self.url,
+            project_id(repo)
+        ))?;
+
+        api.query_pairs_mut().extend_pairs(params);
+
+        Ok(api)
+    }
+}
+
+impl Provider for Gitlab {
+    fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
+        let api = format!("{}/api/v4/projects/{}", self.url, project_id(repo));
+
+        let merge_requests: Vec<MergeRequest> =
+            request_gitlab(&format!("{api}/repository/commits/{sha}/merge_requests"))?;
+
+        match merge_requests.into_iter().next() {
+            Some(merge_request) => Ok(merge_request.into_related_pr(Some(sha.into()))),
+            None => {
+                let commit: Commit = request_gitlab(&format!("{api}/repository/commits/{sha}"))?;
+
+                Ok(RelatedPr {
+                    url: commit.web_url,
+                    pr_id: sha[..7].into(),
+                    author: Some(commit.author_name),
+                    author_link: None,
+                    title: None,
+                    body: None,
+                    merge_commit: Some(sha.into()),
+                    is_pr: false,
+                })
+            }
+        }
+    }
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+        let base = format!("{}/{repo}/-", self.url);
+
+        let link = match diff_tags.prev_tag() {
+            Some(prev) => {
+                format!("{base}/compare/{prev}...{}", diff_tags.new_tag())
+            }
+            None => {
+                format!("{base}/commits/{}", diff_tags.new_tag())
+            }
+        };
+
+        Ok(link)
+    }
+
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
+        Ok(format!("{}/{repo}/-/releases/{tag}", self.url))
+    }
+
+    fn milestone_prs(&self, repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        let api = self.merged_requests_api(repo, &[("milestone", milestone)])?;
+
+        request_gitlab_pages(&api, usize::MAX, |e: MergeRequest| {
+            Some(e.into_related_pr(None))
+        })
+    }
+
+    fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        let api =
+            self.merged_requests_api(repo, &[("order_by", "updated_at"), ("sort", "desc")])?;
+
+        request_gitlab_pages(&api, n, |e: MergeRequest| {
+            Some(e.into_related_pr(None))
+                // fast-forward merges don't have a merge commit
+                .filter(|e| e.merge_commit.is_some())
+        })
+    }
+
+    fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+        let base = &self.url;
+
+        Some(RelatedPr {
+            url: format!("{base}/{repo}/-/commit/{}", raw_commit.sha),
+            pr_id: raw_commit.sha[..7].into(),
+            author: Some(raw_commit.author.clone()),
+            author_link: Some(format!("{base}/{}", raw_commit.author)),
+            title: Some(raw_commit.title.clone()),
+            body: Some(raw_commit.body.clone()),
+            merge_commit: Some(raw_commit.sha.clone()),
+            is_pr: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[ignore = "network"]
+    #[test]
+    fn pr() {
+        let res = Gitlab::new(None)
+            .related_pr("gitlab-org/cli", "8f1b8d2c")
+            .unwrap();
+
+        dbg!(&res);
+    }
+
+    #[test]
+    fn link() {
+        let res = Gitlab::new(Some("https://gitlab.example.com/"))
+            .diff_link(
+                "group/subgroup/project",
+                &DiffTags {
+                    prev: None,
+                    new: Version::new(0, 1, 0),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://gitlab.example.com/group/subgroup/project/-/commits/0.1.0".to_owned()
+        );
+
+        let res = Gitlab::new(Some("https://gitlab.example.com"))
+            .diff_link(
+                "group/project",
+                &DiffTags {
+                    prev: Some(Version::new(0, 1, 0)),
+                    new: Version::new(0, 1, 1),
+                    prefix: String::new(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://gitlab.example.com/group/project/-/compare/0.1.0...0.1.1".to_owned()
+        );
+
+        let res = Gitlab::new(Some("https://gitlab.example.com"))
+            .release_link("group/project", "0.1.1")
+            .unwrap();
+
+        assert_eq!(
+            res,
+            "https://gitlab.example.com/group/project/-/releases/0.1.1".to_owned()
+        );
+    }
+
+    #[test]
+    fn project() {
+        assert_eq!(
+            project_id("group/subgroup/project"),
+            "group%2Fsubgroup%2Fproject".to_owned()
+        );
+    }
+
+    #[test]
+    fn milestone_api() {
+        let api = Gitlab::new(Some("https://gitlab.example.com"))
+            .merged_requests_api("group/project", &[("milestone", "Release 1 & 2")])
+            .unwrap();
+
+        assert_eq!(
+            api.as_str(),
+            "https://gitlab.example.com/api/v4/projects/group%2Fproject/merge_requests?state=merged&per_page=100&milestone=Release+1+%26+2"
+        );
+    }
+
+    #[ignore = "network"]
+    #[test]
+    fn milestone() {
+        let res = Gitlab::new(None)
+            .milestone_prs("gitlab-org/cli", "v1.40.0")
+            .unwrap();
+
+        dbg!(&res);
+    }
+}
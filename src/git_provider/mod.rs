@@ -5,12 +5,22 @@ use changelog::Version;
 
 use crate::repository::RawCommit;
 
+mod bitbucket;
+mod gitea;
 mod github;
+mod gitlab;
 
-#[derive(clap::ValueEnum, Debug, Clone, Default, PartialEq, Eq)]
+#[derive(clap::ValueEnum, Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum GitProvider {
     #[default]
     Github,
+    Gitlab,
+    /// Also works for Forgejo instances.
+    #[value(alias = "forgejo")]
+    #[serde(alias = "forgejo")]
+    Gitea,
+    Bitbucket,
     None,
 }
 // todo: use derive_more::Display when this issue is resolved
@@ -19,6 +29,9 @@ impl Display for GitProvider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GitProvider::Github => write!(f, "github"),
+            GitProvider::Gitlab => write!(f, "gitlab"),
+            GitProvider::Gitea => write!(f, "gitea"),
+            GitProvider::Bitbucket => write!(f, "bitbucket"),
             GitProvider::None => write!(f, "none "),
         }
     }
@@ -41,57 +54,98 @@ pub struct RelatedPr {
 pub struct DiffTags {
     pub prev: Option<Version>,
     pub new: Version,
+    /// Added before the versions to obtain the tag names.
+    pub prefix: String,
 }
 
-impl GitProvider {
-    pub fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
-        match self {
-            GitProvider::Github => github::request_related_pr(repo, sha),
-            GitProvider::None => bail!("No git provider was selected"),
-        }
+impl DiffTags {
+    pub fn new_tag(&self) -> String {
+        format!("{}{}", self.prefix, self.new)
     }
 
-    pub fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
-        match self {
-            GitProvider::Github => github::diff_link(repo, diff_tags),
-            GitProvider::None => bail!("No git provider was selected"),
-        }
+    pub fn prev_tag(&self) -> Option<String> {
+        self.prev
+            .as_ref()
+            .map(|prev| format!("{}{prev}", self.prefix))
     }
+}
 
-    pub fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
-        match self {
-            GitProvider::Github => github::release_link(repo, tag),
-            GitProvider::None => bail!("No git provider was selected"),
-        }
+/// Operations needed from a git forge (Github, Gitlab, ...).
+///
+/// Implement it to use your own backend, or a mock in tests.
+pub trait Provider {
+    fn related_pr(&self, repo: &str, sha: &str) -> anyhow::Result<RelatedPr>;
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String>;
+
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String>;
+
+    fn milestone_prs(&self, _repo: &str, _milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        bail!("Milestones are not supported by this git provider")
     }
 
-    pub fn milestone_prs(&self, repo: &str, milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
-        match self {
-            GitProvider::Github => github::milestone_prs(repo, milestone),
-            GitProvider::None => bail!("No git provider was selected"),
-        }
+    /// Last merged PRs, most recent first.
+    fn last_prs(&self, _repo: &str, _n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        bail!("Listing the last PRs is not supported by this git provider")
     }
 
-    pub fn last_prs(&self, repo: &str, n: usize) -> anyhow::Result<HashMap<String, RelatedPr>> {
-        let prs = match self {
-            GitProvider::Github => github::last_prs(repo, n),
-            GitProvider::None => bail!("No git provider was selected"),
-        }?;
+    /// Fallback function
+    fn offline_related_pr(&self, _repo: &str, _raw_commit: &RawCommit) -> Option<RelatedPr> {
+        None
+    }
 
+    /// Last merged PRs, indexed by their merge commit.
+    fn last_prs_by_merge_commit(
+        &self,
+        repo: &str,
+        n: usize,
+    ) -> anyhow::Result<HashMap<String, RelatedPr>> {
         let mut hashmap = HashMap::new();
 
-        for pr in prs {
-            hashmap.insert(pr.merge_commit.clone().unwrap(), pr);
+        for pr in self.last_prs(repo, n)? {
+            if let Some(merge_commit) = &pr.merge_commit {
+                hashmap.insert(merge_commit.clone(), pr);
+            }
         }
 
         Ok(hashmap)
     }
+}
 
-    /// Fallback function
-    pub fn offline_related_pr(&self, repo: &str, raw_commit: &RawCommit) -> Option<RelatedPr> {
+/// Used when no git provider was selected.
+pub struct NoProvider;
+
+impl Provider for NoProvider {
+    fn related_pr(&self, _repo: &str, _sha: &str) -> anyhow::Result<RelatedPr> {
+        bail!("No git provider was selected")
+    }
+
+    fn diff_link(&self, _repo: &str, _diff_tags: &DiffTags) -> anyhow::Result<String> {
+        bail!("No git provider was selected")
+    }
+
+    fn release_link(&self, _repo: &str, _tag: &str) -> anyhow::Result<String> {
+        bail!("No git provider was selected")
+    }
+
+    fn milestone_prs(&self, _repo: &str, _milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        bail!("No git provider was selected")
+    }
+
+    fn last_prs(&self, _repo: &str, _n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        bail!("No git provider was selected")
+    }
+}
+
+impl GitProvider {
+    /// `url` is the base url of the instance, for self-hosted providers.
+    pub fn provider(&self, url: Option<&str>) -> Box<dyn Provider> {
         match self {
-            GitProvider::Github => github::offline_related_pr(repo, raw_commit),
-            GitProvider::None => None,
+            GitProvider::Github => Box::new(github::Github::new(url)),
+            GitProvider::Gitlab => Box::new(gitlab::Gitlab::new(url)),
+            GitProvider::Gitea => Box::new(gitea::Gitea::new(url)),
+            GitProvider::Bitbucket => Box::new(bitbucket::Bitbucket::new(url)),
+            GitProvider::None => Box::new(NoProvider),
         }
     }
 }
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::Args;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{generate::Response, repository::RawCommit};
+
+/// Commits to leave out of the changelog.
+#[derive(Debug, Clone, Default, Args, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct IgnoreRules {
+    /// Ignore the commits whose title match this regex.
+    #[arg(long = "ignore-title", value_name = "REGEX")]
+    pub title: Vec<String>,
+    /// Ignore the commits whose body match this regex.
+    #[arg(long = "ignore-body", value_name = "REGEX")]
+    pub body: Vec<String>,
+    /// Ignore the commits of the authors matching this regex. Example: 'dependabot|renovate'.
+    #[arg(long = "ignore-author", value_name = "REGEX")]
+    pub author: Vec<String>,
+    /// Ignore merge commits.
+    #[arg(long = "ignore-merge-commits")]
+    pub merge_commits: bool,
+    /// Ignore the commits reverted in the same range, and their revert. Enabled by default.
+    #[arg(
+        long = "ignore-reverts",
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_reverts"
+    )]
+    pub reverts: Option<bool>,
+    /// Keep the reverted commits and their revert, even if ignored in the configuration file.
+    #[arg(long = "no-ignore-reverts", overrides_with = "reverts")]
+    #[serde(skip)]
+    pub no_reverts: bool,
+    /// Ignore the commits touching only these paths. Example: 'docs'.
+    #[arg(long = "ignore-paths", value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+}
+
+impl IgnoreRules {
+    /// Rules of the command line are added to the rules of the configuration file.
+    pub fn merge(&mut self, other: &IgnoreRules) {
+        self.title.extend(other.title.iter().cloned());
+        self.body.extend(other.body.iter().cloned());
+        self.author.extend(other.author.iter().cloned());
+        self.merge_commits |= other.merge_commits;
+        self.reverts = self
+            .reverts
+            .take()
+            .or(self.no_reverts.then_some(false))
+            .or(other.reverts);
+        self.paths.extend(other.paths.iter().cloned());
+    }
+}
+
+/// Compiled version of [`IgnoreRules`].
+#[derive(Debug, Clone, Default)]
+pub struct Ignore {
+    title: Vec<Regex>,
+    body: Vec<Regex>,
+    author: Vec<Regex>,
+    merge_commits: bool,
+    reverts: bool,
+    paths: Vec<PathBuf>,
+}
+
+impl Ignore {
+    pub fn new(rules: &IgnoreRules) -> anyhow::Result<Self> {
+        let regexes = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Regex::new(pattern).map_err(|e| anyhow!("Invalid regex {pattern}: {e}"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            title: regexes(&rules.title)?,
+            body: regexes(&rules.body)?,
+            author: regexes(&rules.author)?,
+            merge_commits: rules.merge_commits,
+            reverts: rules.reverts.unwrap_or(!rules.no_reverts),
+            paths: rules.paths.clone(),
+        })
+    }
+
+    /// Cancel the commits reverted in the same range.
+    pub fn reverts(&self) -> bool {
+        self.reverts
+    }
+
+    pub(crate) fn commit(&self, raw: &RawCommit) -> Response {
+        let reason = if let Some(regex) = self.title.iter().find(|e| e.is_match(&raw.title)) {
+            format!("The title matches {regex}.")
+        } else if let Some(regex) = self.body.iter().find(|e| e.is_match(&raw.body)) {
+            format!("The body matches {regex}.")
+        } else if let Some(regex) = self.author.iter().find(|e| e.is_match(&raw.author)) {
+            format!("The author {} matches {regex}.", raw.author)
+        } else if self.merge_commits && raw.is_merge() {
+            "It is a merge commit.".into()
+        } else if !self.paths.is_empty()
+            && !raw.list_files.is_empty()
+            && raw.list_files.iter().all(|file| {
+                self.paths
+                    .iter()
+                    .any(|path| Path::new(file).starts_with(path))
+            })
+        {
+            "It only touches ignored paths.".into()
+        } else {
+            return Response::No;
+        };
+
+        Response::Yes { reason }
+    }
+
+    pub fn filter(&self, commits: Vec<RawCommit>) -> Vec<RawCommit> {
+        commits
+            .into_iter()
+            .filter(|raw| match self.commit(raw) {
+                Response::Yes { reason } => {
+                    debug!("ignore commit {}: {reason}", raw.short_commit());
+                    false
+                }
+                Response::No => true,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn raw(title: &str, author: &str, files: &[&str]) -> RawCommit {
+        RawCommit {
+            author: author.into(),
+            title: title.into(),
+            body: "".into(),
+            sha: title.into(),
+            list_files: files.iter().map(ToString::to_string).collect(),
+            parents: vec![],
+        }
+    }
+
+    #[test]
+    fn ignore() {
+        let ignore = Ignore::new(&IgnoreRules {
+            title: vec!["^wip".into()],
+            author: vec!["dependabot|renovate".into()],
+            merge_commits: true,
+            paths: vec!["docs".into()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut merge = raw("Merge branch 'main'", "wiiznokes", &[]);
+        merge.parents = vec!["a".into(), "b".into()];
+
+        let commits = vec![
+            raw("feat: 1", "wiiznokes", &["src/lib.rs"]),
+            raw("wip: 2", "wiiznokes", &["src/lib.rs"]),
+            raw("chore: bump serde", "dependabot[bot]", &["Cargo.lock"]),
+            merge,
+            raw("docs: 3", "wiiznokes", &["docs/README.md"]),
+            raw("docs: 4", "wiiznokes", &["docs/README.md", "src/lib.rs"]),
+            raw("fix: 5", "wiiznokes", &["src/lib.rs"]),
+        ];
+
+        let titles = ignore
+            .filter(commits)
+            .into_iter()
+            .map(|e| e.title)
+            .collect::<Vec<_>>();
+
+        assert_eq!(titles, vec!["feat: 1", "docs: 4", "fix: 5"]);
+    }
+}
@@ -1,18 +1,35 @@
-use std::{collections::VecDeque, fs::File, io::Read, path::Path, str::FromStr, sync::LazyLock};
+use std::{fs::File, io::Read, path::Path, str::FromStr, sync::LazyLock};
 
+use anyhow::anyhow;
 use changelog::{de::parse_changelog, ChangeLog, Version};
+use chrono::NaiveDate;
 
 use crate::{
     config::{CommitMessageParsing, Generate},
-    git_provider::GitProvider,
+    git_provider::{DiffTags, GitProvider, NoProvider, Provider, RelatedPr},
+    ignore::IgnoreRules,
     repository::{Period, RawCommit, Repository},
+    template::Templates,
 };
 
 mod test1;
+mod test10;
+mod test11;
+mod test12;
+mod test13;
+mod test2;
+mod test3;
+mod test4;
+mod test5;
+mod test6;
+mod test7;
+mod test8;
+mod test9;
 
 struct Tag {
     pub name: String,
     pub sha: String,
+    pub date: Option<NaiveDate>,
 }
 
 struct FsTest {
@@ -20,102 +37,128 @@ struct FsTest {
     pub tags: Vec<Tag>,
 }
 
-impl Repository for FsTest {
-    fn last_commit_sha(&self) -> String {
-        self.commits.last().unwrap().sha.clone()
-    }
-
-    fn commit_author(&self, sha: &str) -> String {
-        self.commits
-            .iter()
-            .find(|e| e.sha == sha)
-            .unwrap()
-            .author
-            .clone()
-    }
+impl FsTest {
+    fn position(&self, repo_ref: &str) -> anyhow::Result<usize> {
+        let sha = match self.tags.iter().find(|e| e.name == repo_ref) {
+            Some(tag) => &tag.sha,
+            None => repo_ref,
+        };
 
-    fn commit_title(&self, sha: &str) -> String {
         self.commits
             .iter()
-            .find(|e| e.sha == sha)
-            .unwrap()
-            .title
-            .clone()
+            .position(|e| e.sha == sha)
+            .ok_or(anyhow!("unknown ref {repo_ref}"))
     }
+}
 
-    fn commit_body(&self, sha: &str) -> String {
-        self.commits
-            .iter()
-            .find(|e| e.sha == sha)
-            .unwrap()
-            .body
-            .clone()
+impl Repository for FsTest {
+    fn last_commit_sha(&self) -> anyhow::Result<String> {
+        Ok(self.commits.last().unwrap().sha.clone())
     }
 
-    fn commit_files(&self, sha: &str) -> Vec<String> {
-        self.commits
-            .iter()
-            .find(|e| e.sha == sha)
-            .unwrap()
-            .list_files
-            .clone()
+    fn commit(&self, sha: &str) -> anyhow::Result<RawCommit> {
+        Ok(self.commits[self.position(sha)?].clone())
     }
 
-    fn commits_between_tags(&self, tags: &Period) -> Vec<String> {
+    fn commits_between_tags(&self, tags: &Period) -> anyhow::Result<Vec<String>> {
         let mut res = Vec::new();
 
-        let start = tags
-            .since
-            .as_deref()
-            .map(
-                |repo_ref| match self.tags.iter().find(|e| e.name == repo_ref) {
-                    Some(tag) => self.commits.iter().position(|e| e.sha == tag.sha).unwrap(),
-                    None => self.commits.iter().position(|e| e.sha == repo_ref).unwrap(),
-                },
-            )
-            .unwrap_or(0);
-
-        let end = tags
-            .until
-            .as_deref()
-            .map(
-                |repo_ref| match self.tags.iter().find(|e| e.name == repo_ref) {
-                    Some(tag) => self.commits.iter().position(|e| e.sha == tag.sha).unwrap(),
-                    None => self.commits.iter().position(|e| e.sha == repo_ref).unwrap(),
-                },
-            )
-            .unwrap_or(self.commits.len());
+        let start = match &tags.since {
+            Some(repo_ref) => self.position(repo_ref)?,
+            None => 0,
+        };
+
+        let end = match &tags.until {
+            Some(repo_ref) => self.position(repo_ref)?,
+            None => self.commits.len() - 1,
+        };
 
         for e in &self.commits[start..=end] {
             res.push(e.sha.clone());
         }
-        res
+        Ok(res)
+    }
+
+    fn tag_names(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self.tags.iter().map(|e| e.name.clone()).collect())
     }
 
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>> {
+    fn tag_date(&self, tag: &str) -> anyhow::Result<Option<NaiveDate>> {
         Ok(self
             .tags
             .iter()
-            .filter_map(|e| Version::from_str(&e.name).ok())
-            .collect())
+            .find(|e| e.name == tag)
+            .and_then(|e| e.date))
+    }
+}
+
+struct ProviderTest {
+    pub prs: Vec<RelatedPr>,
+}
+
+impl Provider for ProviderTest {
+    fn related_pr(&self, _repo: &str, sha: &str) -> anyhow::Result<RelatedPr> {
+        self.prs
+            .iter()
+            .find(|e| e.merge_commit.as_deref() == Some(sha))
+            .cloned()
+            .ok_or(anyhow!("no pr found for {sha}"))
+    }
+
+    fn diff_link(&self, repo: &str, diff_tags: &DiffTags) -> anyhow::Result<String> {
+        Ok(match &diff_tags.prev {
+            Some(prev) => format!(
+                "https://forge.test/{repo}/compare/{prev}...{}",
+                diff_tags.new
+            ),
+            None => format!("https://forge.test/{repo}/commits/{}", diff_tags.new),
+        })
+    }
+
+    fn release_link(&self, repo: &str, tag: &str) -> anyhow::Result<String> {
+        Ok(format!("https://forge.test/{repo}/releases/{tag}"))
+    }
+
+    fn milestone_prs(&self, _repo: &str, _milestone: &str) -> anyhow::Result<Vec<RelatedPr>> {
+        Ok(self.prs.clone())
+    }
+
+    fn last_prs(&self, _repo: &str, n: usize) -> anyhow::Result<Vec<RelatedPr>> {
+        Ok(self.prs.iter().rev().take(n).cloned().collect())
     }
 }
 
-static DEFAULT_GENERATE: LazyLock<Generate> = LazyLock::new(|| Generate {
+pub static DEFAULT_GENERATE: LazyLock<Generate> = LazyLock::new(|| Generate {
     file: None,
     map: None,
-    parsing: CommitMessageParsing::Smart,
-    exclude_unidentified: true,
-    exclude_not_pr: false,
-    provider: GitProvider::None,
+    map_inline: None,
+    templates: Templates::default(),
+    parsing: Some(CommitMessageParsing::Smart),
+    exclude_unidentified: Some(true),
+    no_exclude_unidentified: false,
+    exclude_not_pr: None,
+    no_exclude_not_pr: false,
+    include_scope: vec![],
+    exclude_scope: vec![],
+    ignore: IgnoreRules::default(),
+    breaking: None,
+    breaking_section: None,
+    provider: Some(GitProvider::None),
+    provider_url: None,
     repo: None,
-    omit_pr_link: false,
-    omit_thanks: false,
+    omit_pr_link: None,
+    no_omit_pr_link: false,
+    omit_thanks: None,
+    no_omit_thanks: false,
     stdout: false,
     specific: None,
     milestone: None,
     since: None,
     until: None,
+    tag_prefix: None,
+    path: None,
+    package: None,
+    workspace: false,
 });
 
 fn raw_commit(title: &str, sha: &str) -> RawCommit {
@@ -125,6 +168,20 @@ fn raw_commit(title: &str, sha: &str) -> RawCommit {
         body: "".to_owned(),
         sha: sha.to_owned(),
         list_files: vec![],
+        parents: vec![],
+    }
+}
+
+fn related_pr(number: u32, author: &str, sha: &str) -> RelatedPr {
+    RelatedPr {
+        url: format!("https://forge.test/wiiznokes/changen/pull/{number}"),
+        pr_id: format!("#{number}"),
+        author: Some(author.to_owned()),
+        author_link: Some(format!("https://forge.test/{author}")),
+        title: None,
+        body: None,
+        merge_commit: Some(sha.to_owned()),
+        is_pr: true,
     }
 }
 
@@ -132,6 +189,7 @@ fn tag(name: &str, sha: &str) -> Tag {
     Tag {
         name: name.to_owned(),
         sha: sha.to_owned(),
+        date: None,
     }
 }
 
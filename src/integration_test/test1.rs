@@ -29,7 +29,7 @@ fn test_repo() {
 
     let changelog = read_changelog("src/integration_test/test1/test1.init").unwrap();
 
-    let output = generate(&r, changelog, &options).unwrap();
+    let output = generate(&r, &NoProvider, changelog, &options, None).unwrap();
 
     let expected = read_file("src/integration_test/test1/test1.expect").unwrap();
 
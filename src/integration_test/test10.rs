@@ -0,0 +1,87 @@
+use crate::generate::generate;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+#[test]
+fn test_reverts() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.since = Some("002".into());
+
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            // already in the changelog
+            raw_commit("feat: parse dates", "001"),
+            raw_commit("chore: update the changelog (skip changelog)", "002"),
+            raw_commit("feat: new command", "003"),
+            raw_commit("fix: parse empty sections", "004"),
+            RawCommit {
+                body: "This reverts commit 003.".into(),
+                ..raw_commit("Revert \"feat: new command\"", "005")
+            },
+            RawCommit {
+                body: "Dates are not ready.\n\nThis reverts commit 001.".into(),
+                ..raw_commit("chore: remove date parsing", "006")
+            },
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test10/test10.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test10/test10.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_reverts_since_last_release() {
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            // added by a previous run
+            raw_commit("feat: new command", "003"),
+            raw_commit("fix: parse empty sections", "004"),
+            RawCommit {
+                body: "This reverts commit 003.".into(),
+                ..raw_commit("Revert \"feat: new command\"", "005")
+            },
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test10/test10_incremental.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+
+    let expected = read_file("src/integration_test/test10/test10_incremental.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_keep_reverts() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.ignore.no_reverts = true;
+
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: new command", "003"),
+            RawCommit {
+                body: "This reverts commit 003.".into(),
+                ..raw_commit("Revert \"feat: new command\"", "005")
+            },
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test10/test10_incremental.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &options, None).unwrap();
+
+    assert!(output.contains("- new command <!-- changen:003 -->"));
+}
@@ -0,0 +1,38 @@
+use crate::generate::generate;
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+#[test]
+fn test_incremental() {
+    let mut r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: parse dates", "001"),
+            raw_commit("fix: parse empty sections", "002"),
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+
+    // new commits on the next push
+    r.commits.push(raw_commit("feat: new command", "003"));
+    r.commits.push(raw_commit("feat: parse dates", "004"));
+
+    let changelog = parse_changelog(&output).unwrap();
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+
+    let expected = read_file("src/integration_test/test11/test11.expect").unwrap();
+
+    assert_eq!(output, expected);
+
+    // nothing new
+    let changelog = parse_changelog(&output).unwrap();
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+
+    assert_eq!(output, expected);
+}
@@ -0,0 +1,58 @@
+use crate::generate::generate;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+#[test]
+fn test_edited_notes() {
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: parse dates", "001"),
+            // edited, and moved to another section
+            raw_commit("fix: stuff", "002"),
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test12/test12.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+
+    let expected = read_file("src/integration_test/test12/test12.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_edited_notes_milestone() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.repo = Some("wiiznokes/changen".into());
+    options.milestone = Some("0.2.0".into());
+
+    let r = FsTest {
+        commits: vec![],
+        tags: vec![],
+    };
+
+    let pr = |number, author, title: &str| RelatedPr {
+        title: Some(title.to_owned()),
+        merge_commit: None,
+        ..related_pr(number, author, "")
+    };
+
+    let p = ProviderTest {
+        prs: vec![
+            pr(13, "contributor", "feat: new command"),
+            pr(14, "wiiznokes", "fix: parse empty sections"),
+        ],
+    };
+
+    let changelog = read_changelog("src/integration_test/test12/test12.init").unwrap();
+
+    let output = generate(&r, &p, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test12/test12_milestone.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
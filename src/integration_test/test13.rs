@@ -0,0 +1,87 @@
+use changelog::de::parse_changelog;
+use pretty_assertions::assert_eq;
+
+use crate::{config::Release, generate::generate, release::release, template::Templates};
+
+use super::*;
+
+#[test]
+fn test_templates() {
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat(cli): 1", "001"),
+            raw_commit("fix: 1", "002"),
+            raw_commit("fix!: 2", "003"),
+        ],
+        tags: vec![
+            tag("0.1.0", "000"),
+            Tag {
+                date: NaiveDate::from_ymd_opt(2024, 5, 1),
+                ..tag("0.2.0", "003")
+            },
+        ],
+    };
+
+    let p = ProviderTest {
+        prs: vec![
+            related_pr(12, "wiiznokes", "001"),
+            related_pr(123, "contributor", "003"),
+        ],
+    };
+
+    let templates = Templates {
+        note: Some(
+            "{{ message }}{% if pr %} ({{ pr }}, @{{ author }}){% else %} ({{ short_sha }}){% endif %}"
+                .into(),
+        ),
+        show_section: None,
+        release_title: Some("From {{ previous_tag }}".into()),
+        footer: Some("[Compare {{ previous_tag }}...{{ tag }}]({{ link }})".into()),
+    };
+
+    let mut options = DEFAULT_GENERATE.clone();
+    options.repo = Some("wiiznokes/changen".into());
+    options.templates = templates.clone();
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &p, changelog, &options, None).unwrap();
+
+    let options = Release {
+        file: None,
+        version: None,
+        bump: None,
+        pre: None,
+        map: None,
+        map_inline: None,
+        breaking_section: None,
+        previous_version: None,
+        date: None,
+        provider: None,
+        provider_url: None,
+        repo: Some("wiiznokes/changen".into()),
+        omit_diff: None,
+        no_omit_diff: false,
+        force: false,
+        header: None,
+        merge_dev_versions: None,
+        templates,
+        stdout: true,
+        tag_prefix: None,
+        path: None,
+        package: None,
+        ignore: Default::default(),
+        workspace: false,
+    };
+
+    let changelog = parse_changelog(&output).unwrap();
+
+    let (version, output) = release(&r, &p, changelog, &options, None).unwrap();
+
+    assert_eq!(version, "0.2.0");
+
+    let expected = read_file("src/integration_test/test13/test13.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
@@ -0,0 +1,37 @@
+use crate::generate::generate;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+#[test]
+fn test_provider() {
+    let mut options = DEFAULT_GENERATE.clone();
+
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: 1", "004"),
+            raw_commit("fix: 1", "001"),
+            raw_commit("feat: 2", "002"),
+            raw_commit("fix: 2", "003"),
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let p = ProviderTest {
+        prs: vec![
+            related_pr(12, "wiiznokes", "001"),
+            related_pr(13, "contributor", "003"),
+        ],
+    };
+
+    options.repo = Some("wiiznokes/changen".into());
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &p, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test2/test2.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
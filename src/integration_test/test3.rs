@@ -0,0 +1,48 @@
+use crate::{config::BreakingChanges, generate::generate};
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+fn repo() -> FsTest {
+    let mut breaking_footer = raw_commit("fix(config): rename the output option", "003");
+    breaking_footer.body =
+        "BREAKING CHANGE: `--output` is now `--file`.\nThe old name is not accepted anymore."
+            .into();
+
+    FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: 1", "001"),
+            raw_commit("feat(api)!: remove the old endpoint", "002"),
+            breaking_footer,
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    }
+}
+
+#[test]
+fn test_breaking_section() {
+    let options = DEFAULT_GENERATE.clone();
+
+    let changelog = read_changelog("src/integration_test/test3/test3.init").unwrap();
+
+    let output = generate(&repo(), &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test3/test3.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_breaking_mark() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.breaking = Some(BreakingChanges::Mark);
+
+    let changelog = read_changelog("src/integration_test/test3/test3.init").unwrap();
+
+    let output = generate(&repo(), &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test3/test3_mark.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
@@ -0,0 +1,141 @@
+use crate::{
+    bump::next_version,
+    config::{BumpKind, MapMessageToSection, DEFAULT_BREAKING_SECTION},
+    generate::generate,
+    repository::TagFormat,
+};
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+fn repo(titles: &[&str]) -> FsTest {
+    let mut commits = vec![raw_commit("chore: release 0.1.0 (skip changelog)", "000")];
+
+    for (i, title) in titles.iter().enumerate() {
+        commits.push(raw_commit(title, &format!("00{}", i + 1)));
+    }
+
+    FsTest {
+        commits,
+        tags: vec![tag("0.1.0", "000")],
+    }
+}
+
+#[test]
+fn test_bump_from_commits() {
+    let map = MapMessageToSection::default();
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let r = repo(&["fix: 1", "feat: 2"]);
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 1, 1));
+
+    let r = repo(&["fix: 1", "feat(api)!: 2"]);
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 2, 0));
+
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Patch,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 1, 1));
+}
+
+#[test]
+fn test_bump_from_unreleased() {
+    let map = MapMessageToSection::default();
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let r = repo(&["fix: 1", "feat!: 2"]);
+
+    let output = generate(&r, &NoProvider, changelog, &DEFAULT_GENERATE, None).unwrap();
+    let changelog = parse_changelog(&output).unwrap();
+
+    // the commits are not used anymore
+    let r = repo(&[]);
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 2, 0));
+}
+
+#[test]
+fn test_bump_pre_release() {
+    let map = MapMessageToSection::default();
+    let mut changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let r = repo(&["feat(api)!: 1"]);
+
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        Some("beta"),
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::from_str("0.2.0-beta.1").unwrap());
+
+    changelog
+        .releases
+        .insert(version, changelog::utils::DEFAULT_UNRELEASED.clone());
+
+    // continue the series of the last pre-release
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        Some("beta"),
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::from_str("0.2.0-beta.2").unwrap());
+
+    // promote to stable
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 2, 0));
+}
@@ -0,0 +1,159 @@
+use changelog::{de::parse_changelog_with_options, CalVerFormat};
+use pretty_assertions::assert_eq;
+
+use crate::{
+    bump::next_version,
+    config::{
+        BumpKind, Generate, MapMessageToSection, MergeDevVersions, Release,
+        DEFAULT_BREAKING_SECTION,
+    },
+    generate::generate,
+    release::release,
+    repository::TagFormat,
+};
+
+use super::*;
+
+fn read_calver_changelog(format: &CalVerFormat) -> ChangeLog {
+    let input = read_file("src/integration_test/test5/test5.init").unwrap();
+
+    parse_changelog_with_options(
+        &input,
+        &changelog::de::Options {
+            calver: Some(format.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_calver_release() {
+    let format = CalVerFormat::from_str("YYYY.0M.MICRO").unwrap();
+    let changelog = read_calver_changelog(&format);
+
+    let r = FsTest {
+        commits: vec![raw_commit("fix: 2", "000")],
+        tags: vec![],
+    };
+
+    let options = Release {
+        file: None,
+        version: Some(Version::from_str("2024.04.1").unwrap()),
+        bump: None,
+        pre: None,
+        map: None,
+        map_inline: None,
+        breaking_section: None,
+        previous_version: None,
+        date: NaiveDate::from_ymd_opt(2024, 4, 25),
+        provider: None,
+        provider_url: None,
+        repo: None,
+        omit_diff: Some(true),
+        no_omit_diff: false,
+        force: false,
+        header: None,
+        merge_dev_versions: Some(MergeDevVersions::Auto),
+        templates: Default::default(),
+        stdout: true,
+        tag_prefix: None,
+        path: None,
+        package: None,
+        ignore: Default::default(),
+        workspace: false,
+    };
+
+    let (version, output) = release(&r, &NoProvider, changelog, &options, Some(&format)).unwrap();
+
+    assert_eq!(version, "2024.04.1");
+    assert_eq!(
+        output,
+        read_file("src/integration_test/test5/test5.expect").unwrap()
+    );
+}
+
+#[test]
+fn test_calver_bump() {
+    let format = CalVerFormat::from_str("YYYY.0M.MICRO").unwrap();
+    let today = chrono::Local::now().date_naive();
+
+    let r = FsTest {
+        commits: vec![raw_commit("fix: 1", "000")],
+        tags: vec![],
+    };
+
+    let tags = TagFormat {
+        prefix: String::new(),
+        calver: Some(format.clone()),
+    };
+
+    let mut changelog = read_calver_changelog(&format);
+    let map = MapMessageToSection::default();
+
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &tags,
+    )
+    .unwrap();
+    assert_eq!(version, Version::CalVer(format.next(None, today).unwrap()));
+
+    changelog
+        .releases
+        .insert(version, changelog::utils::DEFAULT_UNRELEASED.clone());
+
+    // second release of the month
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &tags,
+    )
+    .unwrap();
+    assert_eq!(version.calver_opt().unwrap().parts[2], 1);
+
+    next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        Some("beta"),
+        &map,
+        DEFAULT_BREAKING_SECTION,
+        &tags,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_calver_generate() {
+    let format = CalVerFormat::from_str("YYYY.0M.0D.MICRO").unwrap();
+    let changelog = parse_changelog_with_options(
+        "# Changelog\n\n## [Unreleased]\n\n## [2024.04.25.0]\n\n### Added\n\n- feat 1\n",
+        &changelog::de::Options {
+            calver: Some(format.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let r = FsTest {
+        commits: vec![raw_commit("feat: 1", "111"), raw_commit("fix: 2", "222")],
+        tags: vec![tag("v2024.04.25.0", "111")],
+    };
+
+    let options = Generate {
+        stdout: true,
+        ..DEFAULT_GENERATE.clone()
+    };
+
+    let output = generate(&r, &NoProvider, changelog, &options, Some(&format)).unwrap();
+    assert!(output.contains("- 2 <!-- changen:222 -->"));
+}
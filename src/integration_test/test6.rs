@@ -0,0 +1,84 @@
+use crate::{
+    bump::next_version,
+    config::{BumpKind, MapMessageToSection, DEFAULT_BREAKING_SECTION},
+    generate::generate,
+    repository::TagFormat,
+};
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+fn commits() -> Vec<RawCommit> {
+    vec![
+        raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+        raw_commit("feat: 1", "004"),
+        raw_commit("fix: 1", "001"),
+        raw_commit("feat: 2", "002"),
+        raw_commit("fix: 2", "003"),
+    ]
+}
+
+#[test]
+fn test_detect_v_prefix() {
+    let mut options = DEFAULT_GENERATE.clone();
+
+    let r = FsTest {
+        commits: commits(),
+        tags: vec![tag("v0.1.0", "000"), tag("nightly", "003")],
+    };
+
+    let p = ProviderTest {
+        prs: vec![
+            related_pr(12, "wiiznokes", "001"),
+            related_pr(13, "contributor", "003"),
+        ],
+    };
+
+    options.repo = Some("wiiznokes/changen".into());
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &p, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test2/test2.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_custom_prefix() {
+    let r = FsTest {
+        commits: commits(),
+        tags: vec![tag("other-v1.0.0", "001"), tag("changen-v0.1.0", "000")],
+    };
+
+    let tags = TagFormat::new(&r, Some("changen-v"), None).unwrap();
+
+    assert_eq!(r.tags_list(&tags).unwrap(), vec![Version::new(0, 1, 0)]);
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let version = next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &MapMessageToSection::default(),
+        DEFAULT_BREAKING_SECTION,
+        &tags,
+    )
+    .unwrap();
+    assert_eq!(version, Version::new(0, 1, 1));
+
+    // the tag 0.1.0 doesn't exist
+    next_version(
+        &r,
+        &changelog,
+        &BumpKind::Auto,
+        None,
+        &MapMessageToSection::default(),
+        DEFAULT_BREAKING_SECTION,
+        &TagFormat::default(),
+    )
+    .unwrap_err();
+}
@@ -0,0 +1,106 @@
+use clap::Parser;
+
+use crate::{
+    config::{Cli, Commands},
+    generate::generate,
+    release::release,
+};
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+fn commit_with_files(title: &str, sha: &str, files: &[&str]) -> RawCommit {
+    RawCommit {
+        list_files: files.iter().map(ToString::to_string).collect(),
+        ..raw_commit(title, sha)
+    }
+}
+
+#[test]
+fn test_path_filter() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.path = Some("changelog_document".into());
+    options.tag_prefix = Some("changelog_document-v".into());
+
+    let r = FsTest {
+        commits: vec![
+            commit_with_files(
+                "chore: release 0.1.0 (skip changelog)",
+                "000",
+                &["changelog_document/Cargo.toml"],
+            ),
+            commit_with_files(
+                "feat: parse dates",
+                "001",
+                &["changelog_document/src/de.rs"],
+            ),
+            commit_with_files("feat: new command", "002", &["src/lib.rs"]),
+            commit_with_files(
+                "fix: parse empty sections",
+                "003",
+                &["src/lib.rs", "changelog_document/src/de.rs"],
+            ),
+            commit_with_files("fix: typo", "004", &["changelog_document_old/README.md"]),
+        ],
+        tags: vec![tag("0.3.0", "000"), tag("changelog_document-v0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test7/test7.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_path_release_bump() {
+    let r = FsTest {
+        commits: vec![
+            commit_with_files(
+                "chore: release 0.1.0 (skip changelog)",
+                "000",
+                &["changelog_document/Cargo.toml"],
+            ),
+            commit_with_files("feat: new command", "001", &["src/lib.rs"]),
+            commit_with_files(
+                "feat: parse dates",
+                "002",
+                &["changelog_document/src/de.rs"],
+            ),
+            commit_with_files(
+                "fix: parse empty sections",
+                "003",
+                &["changelog_document/src/de.rs"],
+            ),
+        ],
+        tags: vec![tag("changelog_document-v0.1.0", "000")],
+    };
+
+    let Commands::Release(options) = Cli::parse_from([
+        "changen",
+        "release",
+        "--bump",
+        "auto",
+        "--tag-prefix",
+        "changelog_document-v",
+        "--path",
+        "changelog_document",
+        "--ignore-title",
+        "^feat: parse",
+        "--date",
+        "2024-05-01",
+    ])
+    .command
+    else {
+        panic!("release expected");
+    };
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let (version, _) = release(&r, &NoProvider, changelog, &options, None).unwrap();
+
+    // "feat: new command" is outside of the path, "feat: parse dates" is ignored
+    assert_eq!(version, "0.1.1");
+}
@@ -0,0 +1,46 @@
+use crate::generate::generate;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+fn repo() -> FsTest {
+    FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat(cli): new command", "001"),
+            raw_commit("chore(deps): bump serde to 1.0.200", "002"),
+            raw_commit("fix(release): publish on tags", "003"),
+            raw_commit("refactor(cli): clean the code", "004"),
+            raw_commit("build(deps): update the lock file", "005"),
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    }
+}
+
+#[test]
+fn test_scope_routing() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.exclude_scope = vec!["release".into()];
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&repo(), &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test8/test8.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_include_scope() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.include_scope = vec!["cli".into()];
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&repo(), &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test8/test8_include.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
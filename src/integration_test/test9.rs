@@ -0,0 +1,52 @@
+use crate::generate::generate;
+use pretty_assertions::assert_eq;
+
+use super::*;
+
+#[test]
+fn test_ignore_rules() {
+    let mut options = DEFAULT_GENERATE.clone();
+    options.ignore = IgnoreRules {
+        title: vec!["^wip".into()],
+        author: vec!["dependabot|renovate".into()],
+        merge_commits: true,
+        paths: vec!["docs".into()],
+        ..Default::default()
+    };
+
+    let r = FsTest {
+        commits: vec![
+            raw_commit("chore: release 0.1.0 (skip changelog)", "000"),
+            raw_commit("feat: parse dates", "001"),
+            RawCommit {
+                author: "dependabot[bot]".into(),
+                ..raw_commit("fix(deps): bump serde", "002")
+            },
+            RawCommit {
+                parents: vec!["001".into(), "002".into()],
+                ..raw_commit("Merge branch 'main' into dev", "003")
+            },
+            RawCommit {
+                list_files: vec!["docs/README.md".into()],
+                ..raw_commit("fix: typo in the docs", "004")
+            },
+            raw_commit("wip: new command", "005"),
+            raw_commit("feat: new command", "006"),
+            raw_commit("fix: parse empty sections", "007"),
+            raw_commit("Revert \"feat: new command\"", "008"),
+            RawCommit {
+                body: "Only the CI is affected.\n\n!changelog".into(),
+                ..raw_commit("fix: update the CI", "009")
+            },
+        ],
+        tags: vec![tag("0.1.0", "000")],
+    };
+
+    let changelog = read_changelog("src/integration_test/test2/test2.init").unwrap();
+
+    let output = generate(&r, &NoProvider, changelog, &options, None).unwrap();
+
+    let expected = read_file("src/integration_test/test9/test9.expect").unwrap();
+
+    assert_eq!(output, expected);
+}
@@ -12,24 +12,36 @@ use std::{
 
 use anyhow::bail;
 use changelog::{
-    de::parse_changelog,
+    de::{self, parse_changelog_with_options},
     ser::{serialize_changelog, serialize_release, OptionsRelease},
 };
-use config::{Cli, Commands, MapMessageToSection, New, Remove, Show, Validate};
+use config::{
+    Bump, Cli, Commands, MapMessageToSection, New, Remove, Show, Validate, Yank,
+    DEFAULT_BREAKING_SECTION,
+};
+use config_file::ConfigFile;
 use generate::generate;
-use repository::{Fs, Repository};
+use git_provider::Provider;
+use repository::{CommitFilter, Git, Repository, TagFormat};
 use utils::try_get_repo;
 
 #[macro_use]
 extern crate log;
 
+mod bump;
 mod commit_parser;
 pub mod config;
+mod config_file;
 mod generate;
-mod git_provider;
+pub mod git_provider;
+mod ignore;
 mod release;
-mod repository;
+pub mod repository;
+mod revert;
+mod template;
 mod utils;
+mod workspace;
+mod yank;
 
 #[cfg(test)]
 mod test_res;
@@ -78,52 +90,153 @@ fn write_output(output: &str, path: &Path, stdout: bool) -> anyhow::Result<()> {
 
 #[inline]
 pub fn run(cli: Cli) -> anyhow::Result<()> {
-    let r = Fs;
+    run_generic(Git::open_from_env, None, cli)
+}
 
-    run_generic(&r, cli)
+/// Use this git provider instead of the one selected with `--provider`.
+#[inline]
+pub fn run_with_provider(p: &dyn Provider, cli: Cli) -> anyhow::Result<()> {
+    run_generic(Git::open_from_env, Some(p), cli)
 }
 
-fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
+/// `open_repo` is only called by the commands which need the repository,
+/// so the others also work outside of a git repository.
+pub fn run_generic<R, F>(open_repo: F, p: Option<&dyn Provider>, cli: Cli) -> anyhow::Result<()>
+where
+    R: Repository,
+    F: FnOnce() -> anyhow::Result<R>,
+{
     debug!("is terminal: {}", io::stdin().is_terminal());
     debug!("is terminal stdout: {}", io::stdout().is_terminal());
 
+    let config = ConfigFile::load(cli.config.as_deref())?.unwrap_or_default();
+
+    let calver = cli.calver.or(config.calver.clone());
+
+    let de_options = de::Options {
+        calver: calver.clone(),
+        ..Default::default()
+    };
+
     match cli.command {
+        Commands::Generate(options) if options.workspace => {
+            let r = &open_repo()?;
+            workspace::generate_workspace(r, p, &config, &options, &de_options, calver.as_ref())?;
+        }
+
         Commands::Generate(mut options) => {
+            let r = &open_repo()?;
+            config.apply_generate(&mut options)?;
             let path = get_changelog_path(options.file.clone());
             let input = read_file(&path)?;
-            let changelog = parse_changelog(&input)?;
+            let changelog = parse_changelog_with_options(&input, &de_options)?;
             options.repo = try_get_repo(options.repo);
 
-            let output = generate(r, changelog, &options)?;
+            let provider = options
+                .provider
+                .clone()
+                .unwrap_or_default()
+                .provider(options.provider_url.as_deref());
+
+            let output = generate(
+                r,
+                p.unwrap_or(provider.as_ref()),
+                changelog,
+                &options,
+                calver.as_ref(),
+            )?;
 
             write_output(&output, &path, options.stdout)?;
         }
 
+        Commands::Release(options) if options.workspace => {
+            let r = &open_repo()?;
+            workspace::release_workspace(r, p, &config, &options, &de_options, calver.as_ref())?;
+        }
+
         Commands::Release(mut options) => {
+            let r = &open_repo()?;
+            config.apply_release(&mut options)?;
             let path = get_changelog_path(options.file.clone());
             let input = read_file(&path)?;
-            let changelog = parse_changelog(&input)?;
+            let changelog = parse_changelog_with_options(&input, &de_options)?;
             options.repo = try_get_repo(options.repo);
 
-            let (version, output) = release::release(r, changelog, &options)?;
+            let provider = options
+                .provider
+                .clone()
+                .unwrap_or_default()
+                .provider(options.provider_url.as_deref());
+
+            let (version, output) = release::release(
+                r,
+                p.unwrap_or(provider.as_ref()),
+                changelog,
+                &options,
+                calver.as_ref(),
+            )?;
 
             write_output(&output, &path, options.stdout)?;
 
             eprintln!("New release {} successfully created.", version);
         }
 
-        Commands::Validate(options) => {
+        Commands::Bump(mut options) => {
+            let r = &open_repo()?;
+            config.apply_bump(&mut options)?;
+
+            let Bump {
+                kind,
+                pre,
+                file,
+                map,
+                map_inline,
+                breaking_section,
+                tag_prefix,
+                path: package_path,
+                package: _,
+                ignore,
+            } = options;
+
+            let path = get_changelog_path(file);
+            let input = read_file(&path)?;
+            let changelog = parse_changelog_with_options(&input, &de_options)?;
+
+            let map = MapMessageToSection::try_new(map, map_inline.as_ref())?;
+            let breaking_section = breaking_section
+                .as_deref()
+                .unwrap_or(DEFAULT_BREAKING_SECTION);
+            let tags = TagFormat::new(r, tag_prefix.as_deref(), calver.as_ref())?;
+
+            let version = bump::next_version(
+                &CommitFilter::new(r, package_path, &ignore)?,
+                &changelog,
+                &kind,
+                pre.as_deref(),
+                &map,
+                breaking_section,
+                &tags,
+            )?;
+
+            println!("{version}");
+        }
+
+        Commands::Validate(mut options) => {
+            config.apply_validate(&mut options);
+
             let Validate {
                 file,
                 format,
                 map,
+                map_inline,
+                breaking_section,
                 ast,
                 stdout,
             } = options;
 
             let path = get_changelog_path(file);
             let input = read_file(&path)?;
-            let mut changelog = parse_changelog(&input)?;
+            let mut changelog = parse_changelog_with_options(&input, &de_options)?;
 
             debug!("changelog: {:?}", changelog);
 
@@ -132,8 +245,11 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
             }
 
             if format {
-                let map = MapMessageToSection::try_new(map)?;
-                changelog.sanitize(&map.to_fmt_options());
+                let map = MapMessageToSection::try_new(map, map_inline.as_ref())?;
+                let breaking_section = breaking_section
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BREAKING_SECTION);
+                changelog.sanitize(&map.to_fmt_options(breaking_section));
                 let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
 
                 write_output(&output, &path, stdout)?;
@@ -142,12 +258,19 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
             eprintln!("Changelog parsed with success!");
         }
 
-        Commands::Show(options) => {
-            let Show { file, n, version } = options;
+        Commands::Show(mut options) => {
+            config.apply_show(&mut options);
+
+            let Show {
+                file,
+                n,
+                version,
+                templates,
+            } = options;
 
             let path = get_changelog_path(file);
             let input = read_file(&path)?;
-            let changelog = parse_changelog(&input)?;
+            let changelog = parse_changelog_with_options(&input, &de_options)?;
 
             debug!("changelog: {:?}", changelog);
 
@@ -174,10 +297,23 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
 
             for (pos, release) in releases.iter().enumerate() {
                 debug!("show release: {:?}", release);
+
+                let mut release = release.clone().into_owned();
+                if let Some(template) = &templates.show_section {
+                    for section in release.note_sections.values_mut() {
+                        section.title = template::render(
+                            template,
+                            &template::SectionContext {
+                                title: section.title.clone(),
+                            },
+                        )?;
+                    }
+                }
+
                 let mut output = String::new();
                 serialize_release(
                     &mut output,
-                    release,
+                    &release,
                     &OptionsRelease {
                         serialize_title: false,
                     },
@@ -190,7 +326,9 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
             }
         }
 
-        Commands::New(options) => {
+        Commands::New(mut options) => {
+            config.apply_new(&mut options);
+
             let New { path, force } = options;
 
             let path = get_changelog_path(path);
@@ -211,7 +349,9 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
 
             println!("Changelog successfully created!");
         }
-        Commands::Remove(options) => {
+        Commands::Remove(mut options) => {
+            config.apply_remove(&mut options);
+
             let Remove {
                 file,
                 stdout,
@@ -220,7 +360,7 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
 
             let path = get_changelog_path(file);
             let input = read_file(&path)?;
-            let mut changelog = parse_changelog(&input)?;
+            let mut changelog = parse_changelog_with_options(&input, &de_options)?;
 
             debug!("changelog: {:?}", changelog);
 
@@ -243,6 +383,29 @@ fn run_generic<R: Repository>(r: &R, cli: Cli) -> anyhow::Result<()> {
 
             let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
 
+            write_output(&output, &path, stdout)?;
+        }
+        Commands::Yank(mut options) => {
+            config.apply_yank(&mut options);
+
+            let Yank {
+                file,
+                stdout,
+                version,
+                undo,
+                index,
+            } = options;
+
+            let path = get_changelog_path(file);
+            let input = read_file(&path)?;
+            let mut changelog = parse_changelog_with_options(&input, &de_options)?;
+
+            let index = index.as_deref().map(yank::read_index).transpose()?;
+
+            yank::yank(&mut changelog, version.as_deref(), undo, index.as_ref())?;
+
+            let output = serialize_changelog(&changelog, &changelog::ser::Options::default());
+
             write_output(&output, &path, stdout)?;
         }
     }
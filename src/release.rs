@@ -1,31 +1,77 @@
 use anyhow::bail;
-use changelog::{ser::serialize_changelog, utils::DEFAULT_UNRELEASED, ChangeLog};
+use changelog::{
+    ser::serialize_changelog, utils::DEFAULT_UNRELEASED, CalVerFormat, ChangeLog, Version,
+};
 
 use crate::{
-    config::MergeDevVersions,
-    git_provider::DiffTags,
-    repository::{try_detect_new_version, Repository},
+    bump::{next_version, pre_release_version},
+    config::{MapMessageToSection, MergeDevVersions, DEFAULT_BREAKING_SECTION},
+    git_provider::{DiffTags, Provider},
+    repository::{try_detect_new_version, CommitFilter, Repository, TagFormat},
+    template::{render, ReleaseContext},
+    utils::TextInterpolate,
 };
 
 pub fn release<R: Repository>(
     r: &R,
+    p: &dyn Provider,
     mut changelog: ChangeLog,
     options: &crate::config::Release,
+    calver: Option<&CalVerFormat>,
 ) -> anyhow::Result<(String, String)> {
     let crate::config::Release {
         file: _,
         version,
+        bump,
+        pre,
+        map,
+        map_inline,
+        breaking_section,
         previous_version,
-        provider,
+        date,
+        provider: _,
+        provider_url: _,
         repo,
         header,
         merge_dev_versions,
+        templates,
         omit_diff,
+        no_omit_diff: _,
         stdout: _,
         force,
+        tag_prefix,
+        path,
+        package: _,
+        ignore,
+        workspace: _,
     } = options;
 
-    let new_version = try_detect_new_version(r, version.clone())?;
+    let tags = TagFormat::new(r, tag_prefix.as_deref(), calver)?;
+
+    let version = version
+        .as_ref()
+        .map(|v| Version::parse_with(&v.to_string(), calver))
+        .transpose()?;
+
+    let new_version = match (&version, pre) {
+        (Some(version), Some(pre)) => pre_release_version(&changelog, version, pre)?,
+        (None, _) if bump.is_some() || pre.is_some() => {
+            let map = MapMessageToSection::try_new(map.as_ref(), map_inline.as_ref())?;
+            let breaking_section = breaking_section
+                .as_deref()
+                .unwrap_or(DEFAULT_BREAKING_SECTION);
+            next_version(
+                &CommitFilter::new(r, path.clone(), ignore)?,
+                &changelog,
+                &bump.clone().unwrap_or_default(),
+                pre.as_deref(),
+                &map,
+                breaking_section,
+                &tags,
+            )?
+        }
+        _ => try_detect_new_version(r, version, &tags)?,
+    };
 
     if changelog.releases.contains_key(&new_version) {
         if *force {
@@ -45,18 +91,31 @@ pub fn release<R: Repository>(
         .unwrap_or(DEFAULT_UNRELEASED.clone());
 
     prev_unreleased.title.version = new_version.to_string();
+    let date = match date {
+        Some(date) => *date,
+        None => r
+            .tag_date(&tags.tag(&new_version))?
+            .unwrap_or_else(|| chrono::Local::now().date_naive()),
+    };
+    prev_unreleased.title.date = Some(date);
+    // the markers are only needed to generate the unreleased notes
+    prev_unreleased.clear_sources();
 
     if let Some(header) = header {
+        let mut header = TextInterpolate::new(header.clone(), "{", "}");
+        header.interpolate("VERSION", &new_version.to_string());
+        let header = header.text();
+
         match &prev_unreleased.header {
             Some(prev_header) => {
                 prev_unreleased.header = Some(format!("{}\n\n{}", header, prev_header))
             }
-            None => prev_unreleased.header = Some(header.clone()),
+            None => prev_unreleased.header = Some(header),
         }
     }
 
     if let Some(repo) = &repo {
-        match provider.release_link(repo, &new_version.to_string()) {
+        match p.release_link(repo, &tags.tag(&new_version)) {
             Ok(link) => {
                 prev_unreleased.title.release_link = Some(link);
             }
@@ -66,22 +125,11 @@ pub fn release<R: Repository>(
         }
     }
 
-    match merge_dev_versions {
-        MergeDevVersions::Yes | MergeDevVersions::Auto
-            if let Some(new_version_semver) = new_version.version_opt()
-                && new_version_semver.pre.is_empty() =>
-        {
+    match merge_dev_versions.clone().unwrap_or_default() {
+        MergeDevVersions::Yes | MergeDevVersions::Auto if !new_version.is_pre_release() => {
             let dev_releases = changelog
                 .releases
-                .extract_if(|k, _| {
-                    k.version_opt()
-                        .map(|k| {
-                            k.major == new_version_semver.major
-                                && k.minor == new_version_semver.minor
-                                && k.patch == new_version_semver.patch
-                        })
-                        .unwrap_or(false)
-                })
+                .extract_if(|k, _| k.is_pre_release_of(&new_version))
                 .collect::<Vec<_>>();
 
             for (_, dev_release) in dev_releases {
@@ -96,11 +144,24 @@ pub fn release<R: Repository>(
         .clone()
         .or_else(|| changelog.last_version());
 
-    let diff_tags = DiffTags::new(new_version, previous_version)?;
+    let diff_tags = DiffTags::new(new_version, previous_version, &tags.prefix)?;
 
-    if !omit_diff {
+    let mut context = ReleaseContext {
+        version: diff_tags.new.to_string(),
+        previous_version: diff_tags.prev.as_ref().map(ToString::to_string),
+        tag: diff_tags.new_tag(),
+        previous_tag: diff_tags.prev_tag(),
+        link: None,
+        date: date.to_string(),
+    };
+
+    if let Some(template) = &templates.release_title {
+        prev_unreleased.title.title = Some(render(template, &context)?);
+    }
+
+    if !omit_diff.unwrap_or_default() {
         let link = if let Some(repo) = &repo {
-            match provider.diff_link(repo, &diff_tags) {
+            match p.diff_link(repo, &diff_tags) {
                 Ok(link) => Some(link),
                 Err(e) => {
                     eprintln!("{e}");
@@ -112,7 +173,13 @@ pub fn release<R: Repository>(
         };
 
         if let Some(link) = link {
-            let line = format!("_Full Changelog: {link}_");
+            let line = match &templates.footer {
+                Some(template) => {
+                    context.link = Some(link);
+                    render(template, &context)?
+                }
+                None => format!("_Full Changelog: {link}_"),
+            };
 
             match &mut prev_unreleased.footer {
                 Some(footer) => {
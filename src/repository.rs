@@ -1,9 +1,19 @@
-use std::{collections::VecDeque, process::Command, str::FromStr};
-
-use anyhow::bail;
-use changelog::Version;
-
-use crate::git_provider::DiffTags;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, bail};
+use changelog::{CalVerFormat, Version};
+use chrono::{DateTime, NaiveDate};
+use git2::{Oid, Sort};
+
+use crate::{
+    git_provider::DiffTags,
+    ignore::{Ignore, IgnoreRules},
+    revert::cancel_reverts,
+};
 
 #[derive(Clone, Debug)]
 pub struct RawCommit {
@@ -12,26 +22,29 @@ pub struct RawCommit {
     pub body: String,
     pub sha: String,
     pub list_files: Vec<String>,
+    /// Sha of the parent commits.
+    pub parents: Vec<String>,
 }
 
 impl RawCommit {
-    pub fn last_from_fs<R: Repository>(r: &R) -> Self {
-        let sha = r.last_commit_sha();
-        Self::from_sha(r, &sha)
-    }
-
-    pub fn from_sha<R: Repository>(r: &R, sha: &str) -> Self {
-        Self {
-            author: r.commit_author(sha),
-            title: r.commit_title(sha),
-            body: r.commit_body(sha),
-            list_files: r.commit_files(sha),
-            sha: sha.into(),
-        }
+    pub fn last_from_fs<R: Repository>(r: &R) -> anyhow::Result<Self> {
+        let sha = r.last_commit_sha()?;
+        r.commit(&sha)
     }
 
     pub fn short_commit(&self) -> &str {
-        &self.sha[0..7]
+        &self.sha[..self.sha.len().min(7)]
+    }
+
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    /// One of the changed files is in `path`.
+    pub fn touches(&self, path: &Path) -> bool {
+        self.list_files
+            .iter()
+            .any(|file| Path::new(file).starts_with(path))
     }
 }
 
@@ -44,166 +57,472 @@ pub struct Period {
 }
 
 pub trait Repository {
-    fn last_commit_sha(&self) -> String;
+    fn last_commit_sha(&self) -> anyhow::Result<String>;
+
+    /// `sha` can be any ref (tag, sha, ...).
+    fn commit(&self, sha: &str) -> anyhow::Result<RawCommit>;
+
+    /// Oldest first
+    fn commits_between_tags(&self, tags: &Period) -> anyhow::Result<Vec<String>>;
+
+    /// All the commits of the period, oldest first.
+    fn commits(&self, tags: &Period) -> anyhow::Result<Vec<RawCommit>> {
+        self.commits_between_tags(tags)?
+            .iter()
+            .map(|sha| self.commit(sha))
+            .collect()
+    }
+
+    fn tag_names(&self) -> anyhow::Result<Vec<String>>;
+
+    /// Date of the tag, or of its commit for lightweight tags. `None` if the tag doesn't exist.
+    fn tag_date(&self, _tag: &str) -> anyhow::Result<Option<NaiveDate>> {
+        Ok(None)
+    }
+
+    /// Root of the working tree. The files of the commits are relative to it.
+    fn root(&self) -> anyhow::Result<PathBuf> {
+        Ok(PathBuf::new())
+    }
+
+    /// Tags which are versions. Most recent at the end
+    fn tags_list(&self, tags: &TagFormat) -> anyhow::Result<VecDeque<Version>> {
+        let mut res = Vec::new();
+
+        for tag in self.tag_names()? {
+            let Some(version) = tag.strip_prefix(&tags.prefix) else {
+                debug!("ignore tag without the prefix {}: {tag}", tags.prefix);
+                continue;
+            };
 
-    fn commit_author(&self, sha: &str) -> String;
+            match Version::parse_with(version, tags.calver.as_ref()) {
+                Ok(v) => res.push(v),
+                Err(e) => {
+                    eprintln!("incorrect semver tag {tag}: {e}");
+                }
+            }
+        }
 
-    fn commit_title(&self, sha: &str) -> String;
+        res.sort();
 
-    fn commit_body(&self, sha: &str) -> String;
+        let res = res.into();
 
-    fn commit_files(&self, sha: &str) -> Vec<String>;
+        debug!("tags: {:?}", res);
 
-    fn commits_between_tags(&self, tags: &Period) -> Vec<String>;
+        Ok(res)
+    }
+}
+
+fn git<const N: usize>(args: [&str; N]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("Failed to execute git command: {e}"))?;
+
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim())
+    }
 
-    /// Most recent at the end
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>>;
+    Ok(String::from_utf8(output.stdout)?)
 }
 
-/// Represent the real implementation of the Repository trait
+/// Separate each commit of `git log`, then each field.
+const LOG_FORMAT: &str = "--format=format:%x1e%H%x00%P%x00%an%x00%s%x00%b%x00";
+
+fn parse_log(output: &str) -> Vec<RawCommit> {
+    output
+        .split('\x1e')
+        .filter(|e| !e.trim().is_empty())
+        .map(|commit| {
+            let mut fields = commit.splitn(6, '\0');
+            let mut next = || fields.next().unwrap_or_default().trim().to_owned();
+
+            RawCommit {
+                sha: next(),
+                parents: next().split_whitespace().map(ToOwned::to_owned).collect(),
+                author: next(),
+                title: next(),
+                body: next(),
+                list_files: next().lines().map(ToOwned::to_owned).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Represent the real implementation of the Repository trait, by spawning git commands.
 #[derive(Default)]
 pub struct Fs;
 
 impl Repository for Fs {
-    fn last_commit_sha(&self) -> String {
-        let output = Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .output()
-            .expect("Failed to execute git command");
-
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
-        }
+    fn last_commit_sha(&self) -> anyhow::Result<String> {
+        Ok(git(["rev-parse", "HEAD"])?.trim().into())
+    }
 
-        String::from_utf8(output.stdout).unwrap().trim().into()
+    fn commit(&self, sha: &str) -> anyhow::Result<RawCommit> {
+        let output = git(["log", "-1", "--name-only", LOG_FORMAT, sha])
+            .map_err(|_| anyhow!("unknown ref {sha}"))?;
+
+        parse_log(&output).pop().ok_or(anyhow!("unknown ref {sha}"))
     }
 
-    fn commit_author(&self, sha: &str) -> String {
-        let output = Command::new("git")
-            .args(["show", "-s", "--pretty=%an", sha])
-            .output()
-            .expect("Failed to execute git command");
+    fn commits_between_tags(&self, tags: &Period) -> anyhow::Result<Vec<String>> {
+        let period = period_range(tags)?;
 
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
-        }
+        let output = git(["log", &period, "--format=format:%H"])
+            .map_err(|e| anyhow!("commits_between_tags error: {e}"))?;
 
-        String::from_utf8(output.stdout)
-            .expect("Failed to parse UTF-8")
+        Ok(output
             .trim()
-            .into()
+            .lines()
+            .rev()
+            .map(ToString::to_string)
+            .collect())
     }
 
-    fn commit_title(&self, sha: &str) -> String {
-        let output = Command::new("git")
-            .args(["show", "-s", "--pretty=%s", sha])
-            .output()
-            .expect("Failed to execute git command");
+    fn commits(&self, tags: &Period) -> anyhow::Result<Vec<RawCommit>> {
+        let period = period_range(tags)?;
 
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
-        }
+        let output = git(["log", "--name-only", LOG_FORMAT, &period])
+            .map_err(|e| anyhow!("commits error: {e}"))?;
+
+        Ok(parse_log(&output).into_iter().rev().collect())
+    }
+
+    fn tag_names(&self) -> anyhow::Result<Vec<String>> {
+        let output = git(["tag"])?;
 
-        String::from_utf8(output.stdout).unwrap().trim().into()
+        Ok(output.trim().lines().map(ToString::to_string).collect())
     }
 
-    fn commit_body(&self, sha: &str) -> String {
-        let output = Command::new("git")
-            .args(["show", "-s", "--pretty=%b", sha])
-            .output()
-            .expect("Failed to execute git command");
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
+    fn tag_date(&self, tag: &str) -> anyhow::Result<Option<NaiveDate>> {
+        let output = git([
+            "for-each-ref",
+            "--format=%(creatordate:short)",
+            &format!("refs/tags/{tag}"),
+        ])?;
+
+        match output.trim() {
+            "" => Ok(None),
+            date => Ok(Some(NaiveDate::parse_from_str(date, "%Y-%m-%d")?)),
         }
+    }
 
-        String::from_utf8(output.stdout).unwrap().trim().into()
+    fn root(&self) -> anyhow::Result<PathBuf> {
+        Ok(git(["rev-parse", "--show-toplevel"])?.trim().into())
     }
+}
 
-    fn commit_files(&self, sha: &str) -> Vec<String> {
-        let output = Command::new("git")
-            .args(["diff-tree", "--no-commit-id", "--name-only", "-r", sha])
-            .output()
-            .expect("Failed to execute git command");
+fn period_range(tags: &Period) -> anyhow::Result<String> {
+    let until = tags.until.as_deref().unwrap_or("HEAD");
+    verify_ref(until)?;
 
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
+    let period = match &tags.since {
+        Some(since) => {
+            verify_ref(since)?;
+            format!("{}..{}", since, until)
         }
+        None => until.to_string(),
+    };
 
-        String::from_utf8(output.stdout)
-            .unwrap()
-            .trim()
-            .lines()
-            .map(ToString::to_string)
-            .collect()
+    Ok(period)
+}
+
+fn verify_ref(repo_ref: &str) -> anyhow::Result<()> {
+    git([
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{repo_ref}^{{commit}}"),
+    ])
+    .map_err(|_| anyhow!("unknown ref {repo_ref}"))?;
+    Ok(())
+}
+
+/// Implementation of the Repository trait backed by libgit2.
+/// Read commits and tags in process, instead of spawning git commands.
+pub struct Git {
+    repo: git2::Repository,
+}
+
+impl Git {
+    /// Open the repository containing the current directory.
+    pub fn open_from_env() -> anyhow::Result<Self> {
+        let repo = git2::Repository::open_from_env()
+            .map_err(|e| anyhow!("Can't open the git repository: {}", e.message()))?;
+
+        Ok(Self { repo })
     }
 
-    fn commits_between_tags(&self, tags: &Period) -> Vec<String> {
-        let until = tags.until.as_deref().unwrap_or("HEAD");
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let repo = git2::Repository::discover(path)
+            .map_err(|e| anyhow!("Can't open the git repository: {}", e.message()))?;
+
+        Ok(Self { repo })
+    }
+
+    fn find_commit(&self, repo_ref: &str) -> anyhow::Result<git2::Commit<'_>> {
+        let commit = self
+            .repo
+            .revparse_single(repo_ref)
+            .and_then(|e| e.peel_to_commit())
+            .map_err(|_| anyhow!("unknown ref {repo_ref}"))?;
+
+        Ok(commit)
+    }
+
+    fn raw_commit(&self, commit: &git2::Commit<'_>) -> anyhow::Result<RawCommit> {
+        Ok(RawCommit {
+            author: commit.author().name().unwrap_or_default().trim().into(),
+            title: commit.summary().unwrap_or_default().trim().into(),
+            body: commit.body().unwrap_or_default().trim().into(),
+            sha: commit.id().to_string(),
+            list_files: self.commit_files(commit)?,
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+        })
+    }
+
+    /// Like `git diff-tree`, merge commits have no files.
+    fn commit_files(&self, commit: &git2::Commit<'_>) -> anyhow::Result<Vec<String>> {
+        if commit.parent_count() > 1 {
+            return Ok(Vec::new());
+        }
 
-        let period = match &tags.since {
-            Some(since) => format!("{}..{}", since, until),
-            None => until.to_string(),
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
         };
 
-        let output = Command::new("git")
-            .args(["log", "--oneline", &period, "--format=format:%H"])
-            .output()
-            .expect("Failed to execute git command");
+        let diff =
+            self.repo
+                .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+        let files = diff
+            .deltas()
+            .filter_map(|delta| {
+                delta
+                    .new_file()
+                    .path()
+                    .or(delta.old_file().path())
+                    .map(|path| path.to_string_lossy().to_string())
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    fn revwalk(&self, tags: &Period) -> anyhow::Result<Vec<Oid>> {
+        let until = tags.until.as_deref().unwrap_or("HEAD");
 
-        if !output.status.success() {
-            panic!(
-                "commits_between_tags error: {}",
-                String::from_utf8_lossy(&output.stderr)
-            )
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+        revwalk.push(self.find_commit(until)?.id())?;
+
+        if let Some(since) = &tags.since {
+            revwalk.hide(self.find_commit(since)?.id())?;
         }
 
-        String::from_utf8(output.stdout)
-            .unwrap()
-            .trim()
-            .lines()
-            .rev()
-            .map(ToString::to_string)
+        Ok(revwalk.collect::<Result<_, _>>()?)
+    }
+}
+
+impl Repository for Git {
+    fn last_commit_sha(&self) -> anyhow::Result<String> {
+        Ok(self.find_commit("HEAD")?.id().to_string())
+    }
+
+    fn commit(&self, sha: &str) -> anyhow::Result<RawCommit> {
+        self.raw_commit(&self.find_commit(sha)?)
+    }
+
+    fn commits_between_tags(&self, tags: &Period) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .revwalk(tags)?
+            .into_iter()
+            .map(|oid| oid.to_string())
+            .collect())
+    }
+
+    fn commits(&self, tags: &Period) -> anyhow::Result<Vec<RawCommit>> {
+        self.revwalk(tags)?
+            .into_iter()
+            .map(|oid| self.raw_commit(&self.repo.find_commit(oid)?))
             .collect()
     }
 
-    fn tags_list(&self) -> anyhow::Result<VecDeque<Version>> {
-        let output = Command::new("git")
-            .arg("tag")
-            .output()
-            .expect("Failed to execute git command");
+    fn tag_names(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .repo
+            .tag_names(None)?
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect())
+    }
 
-        if !output.status.success() {
-            panic!("{}", String::from_utf8_lossy(&output.stderr))
+    fn tag_date(&self, tag: &str) -> anyhow::Result<Option<NaiveDate>> {
+        let Ok(object) = self.repo.revparse_single(&format!("refs/tags/{tag}")) else {
+            return Ok(None);
+        };
+
+        let time = match object.as_tag().and_then(|tag| tag.tagger()) {
+            Some(tagger) => tagger.when(),
+            None => object.peel_to_commit()?.time(),
+        };
+
+        let seconds = time.seconds() + i64::from(time.offset_minutes()) * 60;
+
+        Ok(DateTime::from_timestamp(seconds, 0).map(|date| date.date_naive()))
+    }
+
+    fn root(&self) -> anyhow::Result<PathBuf> {
+        match self.repo.workdir() {
+            Some(workdir) => Ok(workdir.to_path_buf()),
+            None => bail!("The git repository has no working tree"),
         }
+    }
+}
 
-        let mut tags = Vec::new();
+/// Only list the commits touching `path` (the package of a monorepo), when defined,
+/// and not matching the ignore rules.
+pub struct CommitFilter<'a, R> {
+    pub repo: &'a R,
+    pub path: Option<PathBuf>,
+    pub ignore: Ignore,
+}
 
-        for tag in String::from_utf8(output.stdout)?.trim().lines() {
-            match Version::from_str(tag) {
-                Ok(v) => tags.push(v),
-                Err(e) => {
-                    eprintln!("incorrect semver tag {tag}: {e}");
+impl<'a, R: Repository> CommitFilter<'a, R> {
+    pub fn new(repo: &'a R, path: Option<PathBuf>, ignore: &IgnoreRules) -> anyhow::Result<Self> {
+        Ok(Self {
+            repo,
+            path,
+            ignore: Ignore::new(ignore)?,
+        })
+    }
+
+    /// Commits of the period. The commits reverted in the period are left out with their revert,
+    /// and returned apart.
+    pub fn commits_and_reverted(
+        &self,
+        tags: &Period,
+    ) -> anyhow::Result<(Vec<RawCommit>, Vec<RawCommit>)> {
+        let mut commits = self.repo.commits(tags)?;
+
+        if let Some(path) = &self.path {
+            commits.retain(|commit| {
+                let touches = commit.touches(path);
+                if !touches {
+                    debug!(
+                        "commit {} doesn't touch {}",
+                        commit.short_commit(),
+                        path.display()
+                    );
                 }
-            }
+                touches
+            });
+        }
+
+        let commits = self.ignore.filter(commits);
+
+        if self.ignore.reverts() {
+            Ok(cancel_reverts(commits))
+        } else {
+            Ok((commits, Vec::new()))
         }
+    }
+}
+
+impl<R: Repository> Repository for CommitFilter<'_, R> {
+    fn last_commit_sha(&self) -> anyhow::Result<String> {
+        self.repo.last_commit_sha()
+    }
+
+    fn commit(&self, sha: &str) -> anyhow::Result<RawCommit> {
+        self.repo.commit(sha)
+    }
+
+    fn commits_between_tags(&self, tags: &Period) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .commits(tags)?
+            .into_iter()
+            .map(|commit| commit.sha)
+            .collect())
+    }
+
+    fn commits(&self, tags: &Period) -> anyhow::Result<Vec<RawCommit>> {
+        Ok(self.commits_and_reverted(tags)?.0)
+    }
+
+    fn tag_names(&self) -> anyhow::Result<Vec<String>> {
+        self.repo.tag_names()
+    }
+
+    fn tag_date(&self, tag: &str) -> anyhow::Result<Option<NaiveDate>> {
+        self.repo.tag_date(tag)
+    }
 
-        tags.sort();
+    fn root(&self) -> anyhow::Result<PathBuf> {
+        self.repo.root()
+    }
+}
 
-        let tags = tags.into();
+/// How versions are written in the tag names. Example: `v1.2.3`, `mycrate-v0.3.0`.
+#[derive(Debug, Clone, Default)]
+pub struct TagFormat {
+    pub prefix: String,
+    pub calver: Option<CalVerFormat>,
+}
+
+impl TagFormat {
+    /// Without `prefix`, use `v` if most version tags of the repo start with it.
+    pub fn new<R: Repository>(
+        r: &R,
+        prefix: Option<&str>,
+        calver: Option<&CalVerFormat>,
+    ) -> anyhow::Result<Self> {
+        let prefix = match prefix {
+            Some(prefix) => prefix.to_owned(),
+            None => detect_tag_prefix(&r.tag_names()?, calver).to_owned(),
+        };
 
-        debug!("tags: {:?}", tags);
+        debug!("tag prefix: {prefix:?}");
 
-        Ok(tags)
+        Ok(Self {
+            prefix,
+            calver: calver.cloned(),
+        })
+    }
+
+    /// Name of the tag of this version.
+    pub fn tag(&self, version: &Version) -> String {
+        format!("{}{}", self.prefix, version)
+    }
+}
+
+fn detect_tag_prefix(tag_names: &[String], calver: Option<&CalVerFormat>) -> &'static str {
+    let is_version = |s: &str| Version::parse_with(s, calver).is_ok();
+
+    let with_v = tag_names
+        .iter()
+        .filter(|tag| tag.strip_prefix('v').is_some_and(is_version))
+        .count();
+
+    let without_v = tag_names.iter().filter(|tag| is_version(tag)).count();
+
+    if with_v > without_v {
+        "v"
+    } else {
+        ""
     }
 }
 
 pub fn try_detect_new_version<R: Repository>(
     r: &R,
     new: Option<Version>,
+    tags: &TagFormat,
 ) -> anyhow::Result<Version> {
     match new {
         Some(new) => Ok(new),
-        None => match r.tags_list()?.pop_back() {
+        None => match r.tags_list(tags)?.pop_back() {
             Some(v) => Ok(v),
             None => {
                 bail!("No version provided. Can't fall back to last tag because there is none.")
@@ -213,7 +532,7 @@ pub fn try_detect_new_version<R: Repository>(
 }
 
 impl DiffTags {
-    pub fn new(new: Version, prev: Option<Version>) -> anyhow::Result<Self> {
+    pub fn new(new: Version, prev: Option<Version>, prefix: &str) -> anyhow::Result<Self> {
         let prev = if let Some(prev) = prev {
             if prev > new {
                 bail!(
@@ -227,7 +546,11 @@ impl DiffTags {
             None
         };
 
-        Ok(DiffTags { prev, new })
+        Ok(DiffTags {
+            prev,
+            new,
+            prefix: prefix.to_owned(),
+        })
     }
 }
 
@@ -240,11 +563,11 @@ mod test {
     fn test() {
         let r = Fs;
 
-        let raw = RawCommit::last_from_fs(&r);
+        let raw = RawCommit::last_from_fs(&r).unwrap();
 
         dbg!(&raw);
 
-        let res = r.tags_list();
+        let res = r.tags_list(&TagFormat::default());
 
         dbg!(&res);
 
@@ -255,4 +578,148 @@ mod test {
 
         dbg!(&res);
     }
+
+    fn commit_file(repo: &git2::Repository, path: &str, message: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(path), message).unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+        let signature = git2::Signature::now("wiiznokes", "wiiznokes@test").unwrap();
+        let parent = repo.head().ok().map(|e| e.peel_to_commit().unwrap());
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn git() {
+        let path = std::env::temp_dir().join(format!("changen-test-git-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+
+        let repo = git2::Repository::init(&path).unwrap();
+
+        let first = commit_file(&repo, "a.txt", "feat: first");
+        repo.tag_lightweight("0.1.0", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+        let second = commit_file(&repo, "b.txt", "fix: second\n\nbody of the commit");
+        let third = commit_file(&repo, "c.txt", "fix: third");
+
+        let r = Git::open(&path).unwrap();
+
+        assert_eq!(r.last_commit_sha().unwrap(), third.to_string());
+
+        let raw = r.commit(&second.to_string()).unwrap();
+        assert_eq!(raw.author, "wiiznokes");
+        assert_eq!(raw.title, "fix: second");
+        assert_eq!(raw.body, "body of the commit");
+        assert_eq!(raw.list_files, vec!["b.txt".to_owned()]);
+        assert_eq!(raw.parents, vec![first.to_string()]);
+
+        let period = Period {
+            since: Some("0.1.0".into()),
+            until: None,
+        };
+        let res = r.commits_between_tags(&period).unwrap();
+        assert_eq!(res, vec![second.to_string(), third.to_string()]);
+
+        let res = r.commits(&period).unwrap();
+        assert_eq!(
+            res.iter().map(|e| e.title.as_str()).collect::<Vec<_>>(),
+            vec!["fix: second", "fix: third"]
+        );
+
+        assert_eq!(
+            r.tags_list(&TagFormat::default()).unwrap(),
+            vec![Version::new(0, 1, 0)]
+        );
+
+        // 2024-05-01
+        let tagger = git2::Signature::new(
+            "wiiznokes",
+            "wiiznokes@test",
+            &git2::Time::new(1714521600, 0),
+        )
+        .unwrap();
+        repo.tag(
+            "0.2.0",
+            &repo.find_object(third, None).unwrap(),
+            &tagger,
+            "0.2.0",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            r.tag_date("0.2.0").unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+        );
+        assert!(r.tag_date("0.1.0").unwrap().is_some());
+        assert_eq!(r.tag_date("0.1.5").unwrap(), None);
+
+        assert_eq!(
+            r.root().unwrap().canonicalize().unwrap(),
+            path.canonicalize().unwrap()
+        );
+
+        assert!(r
+            .commits(&Period {
+                since: Some("0.1.5".into()),
+                until: None,
+            })
+            .is_err_and(|e| e.to_string() == "unknown ref 0.1.5"));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn tag_prefix() {
+        let names = |names: &[&str]| names.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            detect_tag_prefix(&names(&["v0.1.0", "v0.2.0", "0.3.0"]), None),
+            "v"
+        );
+        assert_eq!(
+            detect_tag_prefix(&names(&["0.1.0", "0.2.0", "v0.3.0"]), None),
+            ""
+        );
+        assert_eq!(detect_tag_prefix(&names(&["nightly"]), None), "");
+
+        let tags = TagFormat {
+            prefix: "mycrate-v".into(),
+            calver: None,
+        };
+        assert_eq!(tags.tag(&Version::new(0, 3, 0)), "mycrate-v0.3.0");
+    }
+
+    #[test]
+    fn log() {
+        let output = "\x1eaaaa\0\0wiiznokes\0feat: first\0\0\na.txt\nb.txt\n\n\x1ebbbb\0aaaa cccc\0wiiznokes\0fix: second\0body\nof the commit\n\0";
+
+        let res = parse_log(output);
+
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].sha, "aaaa");
+        assert_eq!(res[0].title, "feat: first");
+        assert_eq!(res[0].body, "");
+        assert_eq!(
+            res[0].list_files,
+            vec!["a.txt".to_owned(), "b.txt".to_owned()]
+        );
+        assert_eq!(res[1].body, "body\nof the commit");
+        assert!(res[1].list_files.is_empty());
+        assert!(res[0].parents.is_empty());
+        assert!(res[1].is_merge());
+    }
 }
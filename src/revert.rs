@@ -0,0 +1,126 @@
+use crate::repository::RawCommit;
+
+/// The commit reverted by a revert commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reverted<'a> {
+    /// From the `This reverts commit <sha>.` line written by `git revert`.
+    pub sha: Option<&'a str>,
+    /// From the `Revert "<title>"` title.
+    pub title: Option<&'a str>,
+}
+
+impl Reverted<'_> {
+    pub fn is(&self, raw: &RawCommit) -> bool {
+        match self.sha {
+            Some(sha) => {
+                !raw.sha.is_empty() && (raw.sha.starts_with(sha) || sha.starts_with(&raw.sha))
+            }
+            None => self.title == Some(raw.title.as_str()),
+        }
+    }
+}
+
+/// The commit reverted by `raw`, if it is a revert.
+pub fn reverted(raw: &RawCommit) -> Option<Reverted<'_>> {
+    let sha = raw.body.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("This reverts commit ")
+            .map(|sha| sha.trim_end_matches('.'))
+            .filter(|sha| !sha.is_empty())
+    });
+
+    let title = raw
+        .title
+        .strip_prefix("Revert \"")
+        .and_then(|title| title.strip_suffix('"'));
+
+    if sha.is_none() && title.is_none() {
+        return None;
+    }
+
+    Some(Reverted { sha, title })
+}
+
+/// Remove the commits reverted in `commits`, with their revert.
+/// The reverts of commits outside of the list are kept.
+/// Return the kept commits, and the reverted ones.
+pub fn cancel_reverts(commits: Vec<RawCommit>) -> (Vec<RawCommit>, Vec<RawCommit>) {
+    let mut kept: Vec<RawCommit> = Vec::with_capacity(commits.len());
+    let mut cancelled = Vec::new();
+
+    for raw in commits {
+        let pos = reverted(&raw).and_then(|reverted| kept.iter().rposition(|e| reverted.is(e)));
+
+        match pos {
+            Some(pos) => {
+                let reverted = kept.remove(pos);
+                debug!(
+                    "commit {} reverts {}",
+                    raw.short_commit(),
+                    reverted.short_commit()
+                );
+                cancelled.push(reverted);
+            }
+            None => kept.push(raw),
+        }
+    }
+
+    (kept, cancelled)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn raw(title: &str, body: &str, sha: &str) -> RawCommit {
+        RawCommit {
+            author: "wiiznokes".into(),
+            title: title.into(),
+            body: body.into(),
+            sha: sha.into(),
+            list_files: vec![],
+            parents: vec![],
+        }
+    }
+
+    #[test]
+    fn revert() {
+        let revert = raw(
+            "Revert \"feat: 1\"",
+            "This reverts commit 1a2b3c4d.\n",
+            "5e6f",
+        );
+        assert_eq!(
+            reverted(&revert),
+            Some(Reverted {
+                sha: Some("1a2b3c4d"),
+                title: Some("feat: 1")
+            })
+        );
+        assert_eq!(reverted(&raw("feat: 1", "", "1a2b")), None);
+
+        let commits = vec![
+            raw("feat: 1", "", "1a2b3c4d"),
+            raw("feat: 1", "", "2222"),
+            raw("fix: 2", "", "3333"),
+            revert,
+            // reverted by title only
+            raw("Revert \"fix: 2\"", "", "4444"),
+            // reverted commit in a previous range
+            raw("undo", "This reverts commit 0000.", "5555"),
+        ];
+
+        let (kept, cancelled) = cancel_reverts(commits);
+
+        assert_eq!(
+            kept.iter().map(|e| e.sha.as_str()).collect::<Vec<_>>(),
+            vec!["2222", "5555"]
+        );
+        assert_eq!(
+            cancelled.iter().map(|e| e.sha.as_str()).collect::<Vec<_>>(),
+            vec!["1a2b3c4d", "3333"]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+/// User templates, rendered with [Tera](https://keats.github.io/tera/docs/#templates).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Templates {
+    /// Text of a generated note, after the scope. See [`NoteContext`].
+    pub note: Option<String>,
+    /// Title of the sections printed by `changen show`. See [`SectionContext`].
+    /// Only used by `show`: the titles written in the changelog must match the section map.
+    pub show_section: Option<String>,
+    /// Title of a new release, after the version. See [`ReleaseContext`].
+    pub release_title: Option<String>,
+    /// Footer of a new release. See [`ReleaseContext`].
+    pub footer: Option<String>,
+}
+
+impl Templates {
+    /// Templates already set are kept, the others are taken from `other`.
+    pub fn merge(&mut self, other: &Templates) {
+        self.note = self.note.take().or(other.note.clone());
+        self.show_section = self.show_section.take().or(other.show_section.clone());
+        self.release_title = self.release_title.take().or(other.release_title.clone());
+        self.footer = self.footer.take().or(other.footer.clone());
+    }
+}
+
+/// Variables of the note template.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NoteContext {
+    pub message: String,
+    pub scope: Option<String>,
+    pub sha: String,
+    pub short_sha: String,
+    /// Example: `#12`.
+    pub pr: Option<String>,
+    pub pr_url: Option<String>,
+    /// Author of the PR, or of the commit.
+    pub author: Option<String>,
+    pub author_link: Option<String>,
+    pub breaking: bool,
+    /// Day of the generation.
+    pub date: String,
+}
+
+/// Variables of the show section template.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SectionContext {
+    pub title: String,
+}
+
+/// Variables of the release title and footer templates.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReleaseContext {
+    pub version: String,
+    pub previous_version: Option<String>,
+    pub tag: String,
+    pub previous_tag: Option<String>,
+    /// Diff link between the two tags.
+    pub link: Option<String>,
+    /// Day of the release.
+    pub date: String,
+}
+
+pub fn render<T: Serialize>(template: &str, context: &T) -> anyhow::Result<String> {
+    let context = Context::from_serialize(context)?;
+
+    Tera::one_off(template, &context, false)
+        .map_err(|e| anyhow::anyhow!("Can't render the template {template:?}: {e:?}"))
+}
+
+/// Current date, as `YYYY-MM-DD`.
+pub fn today() -> String {
+    chrono::Local::now().date_naive().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn note() {
+        let context = NoteContext {
+            message: "parse dates".into(),
+            pr: Some("#123".into()),
+            author: Some("wiiznokes".into()),
+            ..Default::default()
+        };
+
+        let template = "{{ message }}{% if pr %} ({{ pr }}, @{{ author }}){% endif %}";
+
+        assert_eq!(
+            render(template, &context).unwrap(),
+            "parse dates (#123, @wiiznokes)"
+        );
+
+        let context = NoteContext {
+            pr: None,
+            ..context
+        };
+
+        assert_eq!(render(template, &context).unwrap(), "parse dates");
+
+        render("{{ unknown }}", &context).unwrap_err();
+    }
+}
@@ -26,7 +26,10 @@ impl TryFrom<&str> for Repo {
 pub fn try_get_repo(repo: Option<String>) -> Option<String> {
     let repo = match repo {
         Some(repo) => Some(repo),
-        None => std::env::var("GITHUB_REPOSITORY").ok(),
+        None => std::env::var("GITHUB_REPOSITORY")
+            .or_else(|_| std::env::var("CI_PROJECT_PATH"))
+            .or_else(|_| std::env::var("BITBUCKET_REPO_FULL_NAME"))
+            .ok(),
     };
 
     if repo.is_none() {
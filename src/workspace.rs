@@ -0,0 +1,485 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use changelog::{
+    de::{self, parse_changelog_with_options},
+    CalVerFormat, ReleaseSection, ReleaseSectionNote, Version,
+};
+
+use crate::{
+    config::{Generate, Release},
+    config_file::{ConfigFile, PackageConfig},
+    generate::generate,
+    get_changelog_path,
+    git_provider::Provider,
+    repository::Repository,
+    utils::try_get_repo,
+    write_output,
+};
+
+/// Section where the releases of the internal dependencies are mentioned.
+pub const DEPENDENCIES_SECTION: &str = "Dependencies";
+
+/// A crate of a Cargo workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Relative to the root of the workspace.
+    pub path: PathBuf,
+    pub version: Option<Version>,
+    /// Members of the workspace this crate depends on.
+    pub dependencies: Vec<String>,
+    /// Paths of the members nested in this crate, like the crates of a workspace
+    /// whose root is also a package. Their commits are not part of this crate.
+    pub nested: Vec<PathBuf>,
+}
+
+impl Member {
+    /// Changelog at the root of the crate, and `{name}-v` tags.
+    pub fn package(&self, root: &Path) -> PackageConfig {
+        PackageConfig {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            file: Some(root.join(&self.path).join("CHANGELOG.md")),
+            tag_prefix: None,
+        }
+    }
+}
+
+/// Members of the workspace defined in `root/Cargo.toml`.
+pub fn members(root: &Path) -> anyhow::Result<Vec<Member>> {
+    let manifest = read_manifest(&root.join("Cargo.toml"))?;
+
+    let Some(workspace) = manifest.get("workspace") else {
+        bail!(
+            "{} is not a Cargo workspace",
+            root.join("Cargo.toml").display()
+        );
+    };
+
+    let strings = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|e| e.as_array())
+            .map(|e| {
+                e.iter()
+                    .filter_map(|e| e.as_str())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let exclude = strings("exclude")
+        .iter()
+        .map(|e| normalize(Path::new(e)))
+        .collect::<Vec<_>>();
+
+    let mut dirs = Vec::new();
+
+    // the root package is always a member
+    if manifest.contains_key("package") {
+        dirs.push(PathBuf::new());
+    }
+
+    for pattern in strings("members") {
+        for dir in expand_pattern(root, &pattern)? {
+            if !dirs.contains(&dir) && !exclude.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+
+    let workspace_version = workspace
+        .get("package")
+        .and_then(|e| e.get("version"))
+        .and_then(|e| e.as_str());
+
+    let mut members = Vec::new();
+
+    for dir in dirs {
+        let manifest = if dir.as_os_str().is_empty() {
+            manifest.clone()
+        } else {
+            read_manifest(&root.join(&dir).join("Cargo.toml"))?
+        };
+
+        if let Some(member) = member(dir, &manifest, workspace_version)? {
+            members.push(member);
+        }
+    }
+
+    let names = members.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+    let paths = members.iter().map(|e| e.path.clone()).collect::<Vec<_>>();
+
+    for member in &mut members {
+        member.dependencies.retain(|e| names.contains(e));
+        member.nested = paths
+            .iter()
+            .filter(|path| **path != member.path && path.starts_with(&member.path))
+            .cloned()
+            .collect();
+    }
+
+    debug!("workspace members: {:?}", members);
+
+    Ok(members)
+}
+
+fn read_manifest(path: &Path) -> anyhow::Result<toml::Table> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Can't read manifest {}: {e}", path.display()))?;
+
+    Ok(toml::from_str(&content)?)
+}
+
+/// `.` is the root of the workspace.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|e| !matches!(e, std::path::Component::CurDir))
+        .collect()
+}
+
+/// Only `dir` and `dir/*` are supported.
+fn expand_pattern(root: &Path, pattern: &str) -> anyhow::Result<Vec<PathBuf>> {
+    let Some(parent) = pattern.strip_suffix("/*") else {
+        return Ok(vec![normalize(Path::new(pattern))]);
+    };
+
+    let mut dirs = Vec::new();
+
+    for entry in fs::read_dir(root.join(parent))? {
+        let entry = entry?;
+
+        if entry.path().join("Cargo.toml").is_file() {
+            dirs.push(normalize(&Path::new(parent).join(entry.file_name())));
+        }
+    }
+
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+fn member(
+    path: PathBuf,
+    manifest: &toml::Table,
+    workspace_version: Option<&str>,
+) -> anyhow::Result<Option<Member>> {
+    let Some(package) = manifest.get("package") else {
+        return Ok(None);
+    };
+
+    let Some(name) = package.get("name").and_then(|e| e.as_str()) else {
+        bail!("No package name in {}", path.join("Cargo.toml").display());
+    };
+
+    let version = match package.get("version") {
+        Some(toml::Value::String(version)) => Some(version.as_str()),
+        Some(toml::Value::Table(version)) if version.contains_key("workspace") => workspace_version,
+        _ => None,
+    };
+
+    let version = version.map(Version::from_str).transpose()?;
+
+    let mut dependencies = Vec::new();
+
+    for key in ["dependencies", "build-dependencies"] {
+        if let Some(deps) = manifest.get(key).and_then(|e| e.as_table()) {
+            for (key, value) in deps {
+                let name = value.get("package").and_then(|e| e.as_str()).unwrap_or(key);
+                dependencies.push(name.to_owned());
+            }
+        }
+    }
+
+    Ok(Some(Member {
+        name: name.to_owned(),
+        path,
+        version,
+        dependencies,
+        nested: vec![],
+    }))
+}
+
+/// Mention the dependencies of `member` which are released at the same time.
+pub fn dependency_notes(
+    member: &Member,
+    released: &HashMap<String, Version>,
+) -> Option<ReleaseSection> {
+    let notes = member
+        .dependencies
+        .iter()
+        .filter_map(|dep| {
+            released.get(dep).map(|version| ReleaseSectionNote {
+                scope: None,
+                message: format!("Updated `{dep}` to {version}"),
+                context: vec![],
+                source: None,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if notes.is_empty() {
+        return None;
+    }
+
+    Some(ReleaseSection {
+        title: DEPENDENCIES_SECTION.into(),
+        notes,
+    })
+}
+
+/// Generate the changelog of every member which has one.
+pub fn generate_workspace<R: Repository>(
+    r: &R,
+    p: Option<&dyn Provider>,
+    config: &ConfigFile,
+    options: &Generate,
+    de_options: &de::Options,
+    calver: Option<&CalVerFormat>,
+) -> anyhow::Result<()> {
+    let root = r.root()?;
+    let members = members(&root)?;
+    let config = config
+        .clone()
+        .with_packages(members.iter().map(|member| member.package(&root)));
+
+    for member in &members {
+        let mut options = Generate {
+            package: Some(member.name.clone()),
+            workspace: false,
+            ..options.clone()
+        };
+        options.ignore.paths.extend(member.nested.iter().cloned());
+        config.apply_generate(&mut options)?;
+
+        let path = get_changelog_path(options.file.clone());
+        if !path.is_file() {
+            eprintln!("{}: no changelog found at {}", member.name, path.display());
+            continue;
+        }
+
+        let changelog = parse_changelog_with_options(&fs::read_to_string(&path)?, de_options)?;
+        options.repo = try_get_repo(options.repo);
+
+        let provider = options
+            .provider
+            .clone()
+            .unwrap_or_default()
+            .provider(options.provider_url.as_deref());
+
+        let output = generate(
+            r,
+            p.unwrap_or(provider.as_ref()),
+            changelog,
+            &options,
+            calver,
+        )?;
+
+        write_output(&output, &path, options.stdout)?;
+    }
+
+    Ok(())
+}
+
+/// Release every member whose version in Cargo.toml is not in its changelog yet.
+pub fn release_workspace<R: Repository>(
+    r: &R,
+    p: Option<&dyn Provider>,
+    config: &ConfigFile,
+    options: &Release,
+    de_options: &de::Options,
+    calver: Option<&CalVerFormat>,
+) -> anyhow::Result<()> {
+    let root = r.root()?;
+    let members = members(&root)?;
+    let config = config
+        .clone()
+        .with_packages(members.iter().map(|member| member.package(&root)));
+
+    let mut to_release = Vec::new();
+
+    for member in &members {
+        let mut options = Release {
+            package: Some(member.name.clone()),
+            workspace: false,
+            ..options.clone()
+        };
+        options.ignore.paths.extend(member.nested.iter().cloned());
+        config.apply_release(&mut options)?;
+
+        let path = get_changelog_path(options.file.clone());
+        if !path.is_file() {
+            eprintln!("{}: no changelog found at {}", member.name, path.display());
+            continue;
+        }
+
+        let Some(version) = &member.version else {
+            eprintln!("{}: no version in Cargo.toml", member.name);
+            continue;
+        };
+
+        let changelog = parse_changelog_with_options(&fs::read_to_string(&path)?, de_options)?;
+
+        if changelog.releases.contains_key(version) {
+            info!("{}: {version} is already released", member.name);
+            continue;
+        }
+
+        options.version = Some(version.clone());
+
+        to_release.push((member, options, path, changelog));
+    }
+
+    let released = to_release
+        .iter()
+        .map(|(member, options, ..)| (member.name.clone(), options.version.clone().unwrap()))
+        .collect::<HashMap<_, _>>();
+
+    for (member, mut options, path, mut changelog) in to_release {
+        if let Some(section) = dependency_notes(member, &released) {
+            changelog
+                .unreleased_or_default()
+                .insert_release_notes([section]);
+        }
+
+        options.repo = try_get_repo(options.repo);
+
+        let provider = options
+            .provider
+            .clone()
+            .unwrap_or_default()
+            .provider(options.provider_url.as_deref());
+
+        let (version, output) = crate::release::release(
+            r,
+            p.unwrap_or(provider.as_ref()),
+            changelog,
+            &options,
+            calver,
+        )?;
+
+        write_output(&output, &path, options.stdout)?;
+
+        eprintln!(
+            "New release {} of {} successfully created.",
+            version, member.name
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn discover() {
+        let root =
+            std::env::temp_dir().join(format!("changen-test-workspace-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        write(
+            &root.join("Cargo.toml"),
+            r#"
+[workspace]
+members = [".", "crates/*"]
+exclude = ["crates/old"]
+
+[workspace.package]
+version = "0.3.0"
+
+[package]
+name = "app"
+version = "1.2.0"
+
+[dependencies]
+lib = { path = "crates/lib" }
+anyhow = "1"
+"#,
+        );
+        write(
+            &root.join("crates/lib/Cargo.toml"),
+            r#"
+[package]
+name = "lib"
+version.workspace = true
+
+[build-dependencies]
+utils = { path = "../utils", package = "lib-utils" }
+"#,
+        );
+        write(
+            &root.join("crates/utils/Cargo.toml"),
+            r#"
+[package]
+name = "lib-utils"
+version = "0.1.0"
+"#,
+        );
+        write(
+            &root.join("crates/old/Cargo.toml"),
+            r#"
+[package]
+name = "old"
+version = "0.1.0"
+"#,
+        );
+
+        let members = members(&root).unwrap();
+
+        assert_eq!(
+            members,
+            vec![
+                Member {
+                    name: "app".into(),
+                    path: PathBuf::new(),
+                    version: Some(Version::new(1, 2, 0)),
+                    dependencies: vec!["lib".into()],
+                    nested: vec!["crates/lib".into(), "crates/utils".into()],
+                },
+                Member {
+                    name: "lib".into(),
+                    path: "crates/lib".into(),
+                    version: Some(Version::new(0, 3, 0)),
+                    dependencies: vec!["lib-utils".into()],
+                    nested: vec![],
+                },
+                Member {
+                    name: "lib-utils".into(),
+                    path: "crates/utils".into(),
+                    version: Some(Version::new(0, 1, 0)),
+                    dependencies: vec![],
+                    nested: vec![],
+                },
+            ]
+        );
+
+        assert_eq!(
+            members[1].package(&root).file,
+            Some(root.join("crates/lib/CHANGELOG.md"))
+        );
+
+        let released = HashMap::from([("lib".to_owned(), Version::new(0, 3, 0))]);
+        let section = dependency_notes(&members[0], &released).unwrap();
+        assert_eq!(section.notes[0].message, "Updated `lib` to 0.3.0");
+        assert_eq!(dependency_notes(&members[1], &released), None);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{anyhow, bail};
+use changelog::ChangeLog;
+use serde::Deserialize;
+
+/// A line of a crates.io-style index file.
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    yanked: bool,
+}
+
+/// Yanked state of each version of the index file.
+pub fn read_index(path: &Path) -> anyhow::Result<HashMap<String, bool>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Can't read index file {}: {e}", path.display()))?;
+
+    parse_index(&content)
+}
+
+fn parse_index(content: &str) -> anyhow::Result<HashMap<String, bool>> {
+    let mut versions = HashMap::new();
+
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: IndexEntry =
+            serde_json::from_str(line).map_err(|e| anyhow!("Invalid index line {line}: {e}"))?;
+
+        versions.insert(entry.vers, entry.yanked);
+    }
+
+    Ok(versions)
+}
+
+/// Mark `version` as yanked, or copy the yanked state of every release found in `index`.
+pub fn yank(
+    changelog: &mut ChangeLog,
+    version: Option<&str>,
+    undo: bool,
+    index: Option<&HashMap<String, bool>>,
+) -> anyhow::Result<()> {
+    let Some(version) = version else {
+        let Some(index) = index else {
+            bail!("No version to yank");
+        };
+
+        for release in changelog.releases.values_mut() {
+            if let Some(yanked) = index.get(release.version()) {
+                debug!("release {}: yanked = {yanked}", release.version());
+                release.title.yanked = *yanked;
+            }
+        }
+
+        return Ok(());
+    };
+
+    if let Some(index) = index {
+        match index.get(version) {
+            Some(yanked) if *yanked == undo => {
+                bail!(
+                    "Version {version} is {}yanked in the index",
+                    if *yanked { "" } else { "not " }
+                );
+            }
+            None => bail!("Version {version} not found in the index"),
+            _ => {}
+        }
+    }
+
+    let Some(release) = changelog
+        .releases
+        .values_mut()
+        .find(|release| release.version() == version)
+    else {
+        bail!("Version {version} not found in the changelog");
+    };
+
+    release.title.yanked = !undo;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use changelog::{de::parse_changelog, ser::serialize_changelog};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHANGELOG: &str = "\
+# Changelog
+
+## [Unreleased]
+
+## [0.2.0] - 2024-02-01
+
+### Fixed
+
+- fix 2
+
+## [0.1.0] - 2024-01-01
+
+### Added
+
+- feat 1
+";
+
+    fn titles(changelog: &ChangeLog) -> Vec<String> {
+        serialize_changelog(changelog, &Default::default())
+            .lines()
+            .filter(|line| line.starts_with("## [0"))
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn yank_version() {
+        let mut changelog = parse_changelog(CHANGELOG).unwrap();
+
+        yank(&mut changelog, Some("0.2.0"), false, None).unwrap();
+
+        assert_eq!(
+            titles(&changelog),
+            vec![
+                "## [0.2.0] - 2024-02-01 [YANKED]",
+                "## [0.1.0] - 2024-01-01"
+            ]
+        );
+
+        // round-trip
+        let mut changelog =
+            parse_changelog(&serialize_changelog(&changelog, &Default::default())).unwrap();
+
+        yank(&mut changelog, Some("0.2.0"), true, None).unwrap();
+
+        assert_eq!(
+            titles(&changelog),
+            vec!["## [0.2.0] - 2024-02-01", "## [0.1.0] - 2024-01-01"]
+        );
+
+        yank(&mut changelog, Some("0.3.0"), false, None).unwrap_err();
+    }
+
+    #[test]
+    fn yank_index() {
+        let index = parse_index(
+            r#"
+{"name":"changen","vers":"0.1.0","deps":[],"cksum":"00","features":{},"yanked":true}
+{"name":"changen","vers":"0.2.0","deps":[],"cksum":"00","features":{},"yanked":false}
+"#,
+        )
+        .unwrap();
+
+        let mut changelog = parse_changelog(CHANGELOG).unwrap();
+
+        yank(&mut changelog, None, false, Some(&index)).unwrap();
+
+        assert_eq!(
+            titles(&changelog),
+            vec![
+                "## [0.2.0] - 2024-02-01",
+                "## [0.1.0] - 2024-01-01 [YANKED]"
+            ]
+        );
+
+        // the index doesn't agree
+        yank(&mut changelog, Some("0.2.0"), false, Some(&index)).unwrap_err();
+        yank(&mut changelog, Some("0.1.0"), true, Some(&index)).unwrap_err();
+        yank(&mut changelog, Some("0.1.0"), false, Some(&index)).unwrap();
+    }
+}